@@ -1,11 +1,82 @@
 use crate::{
     display,
-    display::{ui_amount, ui_timestamp},
+    display::{
+        decimal_to_str, mint_sort_key, ui_amount, ui_timestamp, unpriced_mints, usd_value_or_zero,
+        SortBy,
+    },
     pubkey_str::pubkey,
 };
-use gfx_ssl_v2_interface::LiquidityAccount;
+use anyhow::anyhow;
+use gfx_ssl_v2_interface::{LiquidityAccount, SSLPool};
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use solana_sdk::pubkey::Pubkey;
+use std::{cmp::Reverse, collections::HashMap};
+
+/// Estimate of the native amount of fees that claiming would pay out, or `None` if nothing
+/// is estimated to be claimable. See [LiquidityAccount::estimated_claimable_fees] for why
+/// this is only an estimate.
+pub fn estimated_claim(act: &LiquidityAccount, pool: &SSLPool) -> Option<u64> {
+    Some(act.estimated_claimable_fees(pool)).filter(|&fees| fees > 0)
+}
+
+/// Order liquidity accounts for display. Fees earned and deposits are valued in USD
+/// with `prices`. Liquidity accounts don't record volume.
+pub fn sort_liquidity_accounts(
+    accounts: &mut [(Pubkey, LiquidityAccount)],
+    sort_by: SortBy,
+    prices: &HashMap<Pubkey, Decimal>,
+) -> anyhow::Result<()> {
+    match sort_by {
+        SortBy::Volume => return Err(anyhow!("Liquidity accounts can't be sorted by volume")),
+        SortBy::Fees => accounts.sort_by_cached_key(|(_, act)| {
+            Reverse(usd_value_or_zero(
+                act.total_earned as u128,
+                act.mint,
+                prices,
+            ))
+        }),
+        SortBy::Deposits => accounts.sort_by_cached_key(|(_, act)| {
+            Reverse(usd_value_or_zero(
+                act.amount_deposited as u128,
+                act.mint,
+                prices,
+            ))
+        }),
+        SortBy::Mint => accounts.sort_by_cached_key(|(_, act)| mint_sort_key(act.mint)),
+    }
+    Ok(())
+}
+
+/// Totals across liquidity accounts, in USD.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct LiquidityAccountsSummary {
+    pub accounts: usize,
+    /// According to latest USD oracle prices
+    #[serde(with = "decimal_to_str")]
+    pub total_deposits: Decimal,
+    /// According to latest USD oracle prices
+    #[serde(with = "decimal_to_str")]
+    pub total_earned: Decimal,
+    /// Mints left out of the totals, for lack of a price or decimals
+    pub unpriced_mints: Vec<String>,
+}
+
+impl LiquidityAccountsSummary {
+    pub fn new(accounts: &[(Pubkey, LiquidityAccount)], prices: &HashMap<Pubkey, Decimal>) -> Self {
+        let total = |amount: fn(&LiquidityAccount) -> u64| {
+            accounts.iter().fold(Decimal::ZERO, |total, (_, act)| {
+                total.saturating_add(usd_value_or_zero(amount(act) as u128, act.mint, prices))
+            })
+        };
+        Self {
+            accounts: accounts.len(),
+            total_deposits: total(|act| act.amount_deposited),
+            total_earned: total(|act| act.total_earned),
+            unpriced_mints: unpriced_mints(accounts.iter().map(|(_, act)| act.mint), prices),
+        }
+    }
+}
 
 /// Raw data with serde traits, skipping padding and extra space fields
 #[derive(Serialize, Deserialize, Clone)]
@@ -83,3 +154,89 @@ impl From<&(Pubkey, LiquidityAccount)> for LiquidityAccountUiData {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::{SOL_MINT, USDC_MINT};
+
+    #[test]
+    fn nothing_to_claim() {
+        let pool = SSLPool {
+            total_accumulated_lp_reward: 1_500,
+            total_liquidity_deposits: 4_000_000,
+            ..Default::default()
+        };
+        let act = LiquidityAccount {
+            amount_deposited: 1_000_000,
+            last_observed_tap: 1_500,
+            ..Default::default()
+        };
+        assert_eq!(estimated_claim(&act, &pool), None);
+        let act = LiquidityAccount {
+            last_observed_tap: 500,
+            ..act
+        };
+        assert_eq!(estimated_claim(&act, &pool), Some(250));
+    }
+
+    #[test]
+    fn sorts_liquidity_accounts_by_usd_deposits() {
+        let account = |mint, amount_deposited| {
+            (
+                Pubkey::new_unique(),
+                LiquidityAccount {
+                    mint,
+                    amount_deposited,
+                    ..Default::default()
+                },
+            )
+        };
+        // 100 USDC, 10 SOL and 1,000 USDC
+        let mut accounts = vec![
+            account(USDC_MINT, 100_000_000),
+            account(SOL_MINT, 10_000_000_000),
+            account(USDC_MINT, 1_000_000_000),
+        ];
+        let prices = HashMap::from([(USDC_MINT, Decimal::ONE), (SOL_MINT, Decimal::from(20))]);
+        sort_liquidity_accounts(&mut accounts, SortBy::Deposits, &prices).unwrap();
+        let deposits = accounts
+            .iter()
+            .map(|(_, act)| act.amount_deposited)
+            .collect::<Vec<_>>();
+        assert_eq!(deposits, vec![1_000_000_000, 10_000_000_000, 100_000_000]);
+
+        assert!(sort_liquidity_accounts(&mut accounts, SortBy::Volume, &prices).is_err());
+    }
+
+    #[test]
+    fn summarizes_liquidity_accounts() {
+        let account = |mint, amount_deposited, total_earned| {
+            (
+                Pubkey::new_unique(),
+                LiquidityAccount {
+                    mint,
+                    amount_deposited,
+                    total_earned,
+                    ..Default::default()
+                },
+            )
+        };
+        let unknown = Pubkey::new_unique();
+        let accounts = vec![
+            account(USDC_MINT, 100_000_000, 1_000_000),
+            account(SOL_MINT, 10_000_000_000, 500_000_000),
+            account(unknown, 1_000, 1_000),
+        ];
+        let prices = HashMap::from([(USDC_MINT, Decimal::ONE), (SOL_MINT, Decimal::from(20))]);
+        assert_eq!(
+            LiquidityAccountsSummary::new(&accounts, &prices),
+            LiquidityAccountsSummary {
+                accounts: 3,
+                total_deposits: Decimal::from(300),
+                total_earned: Decimal::from(11),
+                unpriced_mints: vec![unknown.to_string()],
+            }
+        );
+    }
+}
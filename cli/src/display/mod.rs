@@ -9,8 +9,14 @@ pub mod ssl_pool;
 use crate::pubkey_str::pubkey::Pubkey;
 use chrono::{DateTime, NaiveDateTime, Utc};
 use gfx_ssl_v2_interface::utils::token_amount;
+use rust_decimal::Decimal;
 use serde::Serialize;
 use solana_sdk::pubkey;
+use std::{
+    collections::HashMap,
+    io::IsTerminal,
+    sync::{Mutex, OnceLock},
+};
 
 pub const MAINNET_POOL_REGISTRY: Pubkey = pubkey!("F451mjRqGEu1azbj46v4FuMEt1CacaPHQKUHzuTqKp4R");
 
@@ -39,6 +45,8 @@ pub fn mint_ui_name(mint: Pubkey) -> Option<String> {
     }
 }
 
+/// Decimals of the known mints, or of any mint whose decimals were cached
+/// earlier in this invocation.
 pub fn mint_decimals(mint: Pubkey) -> Option<u32> {
     match mint {
         USDC_MINT => Some(USDC_DECIMALS),
@@ -47,10 +55,37 @@ pub fn mint_decimals(mint: Pubkey) -> Option<u32> {
         MSOL_MINT => Some(MSOL_DECIMALS),
         SOL_MINT => Some(SOL_DECIMALS),
         JITOSOL_MINT => Some(JITOSOL_DECIMALS),
-        _ => None,
+        _ => cached_mint_decimals().lock().unwrap().get(&mint).copied(),
     }
 }
 
+/// Mint decimals looked up during this invocation, so each mint is fetched at most once.
+fn cached_mint_decimals() -> &'static Mutex<HashMap<Pubkey, u32>> {
+    static CACHE: OnceLock<Mutex<HashMap<Pubkey, u32>>> = OnceLock::new();
+    CACHE.get_or_init(Default::default)
+}
+
+/// Record a mint's decimals, e.g. as read from its SSL pool.
+pub fn cache_mint_decimals(mint: Pubkey, decimals: u32) {
+    cached_mint_decimals()
+        .lock()
+        .unwrap()
+        .insert(mint, decimals);
+}
+
+/// A mint's decimals, calling `fetch` only if they aren't known or cached yet.
+pub fn mint_decimals_or_fetch(
+    mint: Pubkey,
+    fetch: impl FnOnce(&Pubkey) -> anyhow::Result<u32>,
+) -> anyhow::Result<u32> {
+    if let Some(decimals) = mint_decimals(mint) {
+        return Ok(decimals);
+    }
+    let decimals = fetch(&mint)?;
+    cache_mint_decimals(mint, decimals);
+    Ok(decimals)
+}
+
 pub fn ui_amount(raw_amount: u64, mint_decimals: Option<u32>) -> Option<String> {
     mint_decimals.map(|decimals| token_amount::to_ui(raw_amount, decimals).to_string())
 }
@@ -59,6 +94,13 @@ pub fn u128_ui_amount(raw_amount: u128, mint_decimals: Option<u32>) -> Option<St
     mint_decimals.map(|decimals| token_amount::u128_to_ui(raw_amount, decimals).to_string())
 }
 
+/// USD value of a native token amount. Saturates if the value overflows.
+pub fn value_in_usd(native_amount: u64, decimals: u32, price: Decimal) -> Decimal {
+    token_amount::to_ui(native_amount, decimals)
+        .checked_mul(price)
+        .unwrap_or(Decimal::MAX)
+}
+
 pub fn ui_timestamp(raw_timestamp: i64) -> String {
     DateTime::<Utc>::from_naive_utc_and_offset(
         NaiveDateTime::from_timestamp_opt(raw_timestamp, 0).unwrap(),
@@ -67,12 +109,98 @@ pub fn ui_timestamp(raw_timestamp: i64) -> String {
     .to_string()
 }
 
+/// Nominal slot duration, used when slot times can't be sampled.
+pub const DEFAULT_SECONDS_PER_SLOT: f64 = 0.4;
+
+/// Estimate the wall-clock duration of a number of slots, using the average slot duration
+/// between the earliest and latest of the given `(slot, block_time)` samples.
+/// Falls back to [DEFAULT_SECONDS_PER_SLOT] if there aren't two usable samples.
+pub fn estimate_seconds_from_slots(slots: u64, samples: &[(u64, i64)]) -> f64 {
+    let earliest = samples.iter().min_by_key(|(slot, _)| *slot);
+    let latest = samples.iter().max_by_key(|(slot, _)| *slot);
+    let seconds_per_slot = match (earliest, latest) {
+        (Some((first_slot, first_time)), Some((last_slot, last_time)))
+            if last_slot > first_slot && last_time >= first_time =>
+        {
+            (last_time - first_time) as f64 / (last_slot - first_slot) as f64
+        }
+        _ => DEFAULT_SECONDS_PER_SLOT,
+    };
+    slots as f64 * seconds_per_slot
+}
+
+/// When to color non-JSON output.
+#[derive(clap::ArgEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Color if stdout is a terminal and `NO_COLOR` is not set.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    pub fn enabled(self) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                std::env::var_os("NO_COLOR").map_or(true, |v| v.is_empty())
+                    && std::io::stdout().is_terminal()
+            }
+        }
+    }
+}
+
+/// How bulk display commands order their records.
+#[derive(clap::ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortBy {
+    /// Historical USD volume, largest first.
+    Volume,
+    /// USD value of fees generated or earned, largest first.
+    Fees,
+    /// USD value of deposited liquidity, largest first.
+    Deposits,
+    /// Mint name, or address for unknown mints.
+    Mint,
+}
+
+impl SortBy {
+    /// Whether sorting needs the latest prices, to compare USD values.
+    pub fn uses_prices(self) -> bool {
+        matches!(self, SortBy::Fees | SortBy::Deposits)
+    }
+}
+
+/// Sort key for a mint: its name, or its address if the mint isn't known.
+pub fn mint_sort_key(mint: Pubkey) -> String {
+    mint_ui_name(mint).unwrap_or_else(|| mint.to_string())
+}
+
+/// [value_in_usd] of a native token amount, for sorting and totals. Zero when the mint's price
+/// or decimals are unknown, so that those records sort last. Saturates if the value overflows,
+/// including amounts beyond `u64::MAX`.
+pub fn usd_value_or_zero(
+    native_amount: u128,
+    mint: Pubkey,
+    prices: &HashMap<Pubkey, Decimal>,
+) -> Decimal {
+    let (Some(decimals), Some(price)) = (mint_decimals(mint), prices.get(&mint)) else {
+        return Decimal::ZERO;
+    };
+    u64::try_from(native_amount).map_or(Decimal::MAX, |amount| {
+        value_in_usd(amount, decimals, *price)
+    })
+}
+
 pub trait CliDisplay: Serialize {
     fn to_json(&self) -> serde_json::Value;
 
     fn to_json_str_pretty(&self) -> String;
 
     fn cli_pretty_print(&self) -> String;
+
+    fn cli_pretty_print_colored(&self, color: bool) -> String;
 }
 
 impl<T: Serialize> CliDisplay for T {
@@ -87,13 +215,33 @@ impl<T: Serialize> CliDisplay for T {
     fn cli_pretty_print(&self) -> String {
         pretty_printer::cli_pretty_print(&self)
     }
+
+    fn cli_pretty_print_colored(&self, color: bool) -> String {
+        pretty_printer::cli_pretty_print_colored(&self, color)
+    }
+}
+
+/// Serialize a [Decimal] as a string, keeping its exact value in JSON.
+pub mod decimal_to_str {
+    use rust_decimal::Decimal;
+    use serde::{self, Serializer};
+
+    pub fn serialize<S>(decimal: &Decimal, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let s = format!("{}", decimal);
+        serializer.serialize_str(&s)
+    }
 }
 
 /// Display either raw or UI data, JSON formatted or not.
+/// JSON output is never colored.
 pub fn cli_display<'a, T, Raw: Serialize + From<&'a T>, Ui: Serialize + From<&'a T>>(
     values: &'a [T],
     raw: bool,
     json: bool,
+    color: bool,
 ) -> Result<(), serde_json::Error> {
     if raw {
         let values = values.iter().map(|act| Raw::from(act)).collect::<Vec<_>>();
@@ -102,7 +250,7 @@ pub fn cli_display<'a, T, Raw: Serialize + From<&'a T>, Ui: Serialize + From<&'a
         } else {
             values
                 .iter()
-                .for_each(|v| println!("{}", v.cli_pretty_print()));
+                .for_each(|v| println!("{}", v.cli_pretty_print_colored(color)));
         }
     } else {
         let values = values.iter().map(|act| Ui::from(act)).collect::<Vec<_>>();
@@ -111,8 +259,136 @@ pub fn cli_display<'a, T, Raw: Serialize + From<&'a T>, Ui: Serialize + From<&'a
         } else {
             values
                 .iter()
-                .for_each(|v| println!("{}", v.cli_pretty_print()));
+                .for_each(|v| println!("{}", v.cli_pretty_print_colored(color)));
         }
     };
     Ok(())
 }
+
+/// Like [cli_display], followed by a summary of the values. In JSON format,
+/// the values are output as the `items` of an object, next to the `summary`.
+pub fn cli_display_with_summary<
+    'a,
+    T,
+    Raw: Serialize + From<&'a T>,
+    Ui: Serialize + From<&'a T>,
+    Summary: Serialize,
+>(
+    values: &'a [T],
+    summary: &Summary,
+    raw: bool,
+    json: bool,
+    color: bool,
+) -> Result<(), serde_json::Error> {
+    if json {
+        let items = if raw {
+            serde_json::to_value(values.iter().map(Raw::from).collect::<Vec<_>>())?
+        } else {
+            serde_json::to_value(values.iter().map(Ui::from).collect::<Vec<_>>())?
+        };
+        let output = serde_json::json!({ "items": items, "summary": summary });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+    } else {
+        cli_display::<_, Raw, Ui>(values, raw, json, color)?;
+        println!("{}", summary.cli_pretty_print_colored(color));
+    }
+    Ok(())
+}
+
+/// Mints that can't be valued in USD with `prices`, by name or address.
+pub fn unpriced_mints(
+    mints: impl IntoIterator<Item = Pubkey>,
+    prices: &HashMap<Pubkey, Decimal>,
+) -> Vec<String> {
+    let mut unpriced = vec![];
+    for mint in mints {
+        let name = mint_sort_key(mint);
+        if (!prices.contains_key(&mint) || mint_decimals(mint).is_none())
+            && !unpriced.contains(&name)
+        {
+            unpriced.push(name);
+        }
+    }
+    unpriced
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mint_decimals_are_fetched_once() {
+        let mint = Pubkey::new_unique();
+        assert_eq!(mint_decimals(mint), None);
+        let mut fetches = 0;
+        for _ in 0..3 {
+            let decimals = mint_decimals_or_fetch(mint, |_| {
+                fetches += 1;
+                Ok(8)
+            })
+            .unwrap();
+            assert_eq!(decimals, 8);
+        }
+        assert_eq!(fetches, 1);
+        assert_eq!(mint_decimals(mint), Some(8));
+
+        // Known mints and failed fetches aren't cached
+        mint_decimals_or_fetch(USDC_MINT, |_| panic!("USDC decimals are known")).unwrap();
+        let other = Pubkey::new_unique();
+        assert!(mint_decimals_or_fetch(other, |_| Err(anyhow::anyhow!("no mint"))).is_err());
+        assert_eq!(mint_decimals(other), None);
+    }
+
+    #[test]
+    fn usd_value_or_zero_handles_unknown_and_huge_amounts() {
+        let prices = HashMap::from([(SOL_MINT, Decimal::from(20))]);
+        assert_eq!(
+            usd_value_or_zero(1_500_000_000, SOL_MINT, &prices),
+            Decimal::from(30)
+        );
+        // No price, or no decimals
+        assert_eq!(usd_value_or_zero(1, USDC_MINT, &prices), Decimal::ZERO);
+        let unknown = Pubkey::new_unique();
+        let prices = HashMap::from([(unknown, Decimal::ONE)]);
+        assert_eq!(usd_value_or_zero(1, unknown, &prices), Decimal::ZERO);
+        let prices = HashMap::from([(SOL_MINT, Decimal::ONE)]);
+        assert_eq!(
+            usd_value_or_zero(u128::MAX, SOL_MINT, &prices),
+            Decimal::MAX
+        );
+    }
+
+    #[test]
+    fn values_native_amounts_in_usd() {
+        // 1.5 SOL at $20.10
+        assert_eq!(
+            value_in_usd(1_500_000_000, SOL_DECIMALS, Decimal::new(2010, 2)),
+            Decimal::new(3015, 2)
+        );
+        assert_eq!(value_in_usd(0, USDC_DECIMALS, Decimal::ONE), Decimal::ZERO);
+    }
+
+    #[test]
+    fn values_extreme_decimals_in_usd() {
+        assert_eq!(
+            value_in_usd(u64::MAX, 0, Decimal::ONE),
+            Decimal::from(u64::MAX)
+        );
+        // 1 native unit of an 18 decimal token
+        assert_eq!(value_in_usd(1, 18, Decimal::from(2)), Decimal::new(2, 18));
+        assert_eq!(value_in_usd(u64::MAX, 0, Decimal::MAX), Decimal::MAX);
+    }
+
+    #[test]
+    fn estimates_seconds_from_slot_samples() {
+        // 100 slots over 50 seconds
+        let samples = [(1_100, 1_050), (1_000, 1_000), (1_050, 1_020)];
+        assert_eq!(estimate_seconds_from_slots(10, &samples), 5.0);
+    }
+
+    #[test]
+    fn falls_back_to_nominal_slot_time() {
+        assert_eq!(estimate_seconds_from_slots(10, &[]), 4.0);
+        assert_eq!(estimate_seconds_from_slots(10, &[(1_000, 1_000)]), 4.0);
+    }
+}
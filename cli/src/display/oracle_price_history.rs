@@ -1,10 +1,10 @@
 use crate::{display::mint_ui_name, pubkey_str::pubkey};
 use gfx_ssl_v2_interface::{HistoricalPrice, OraclePriceHistory, OracleType};
 use rust_decimal::Decimal;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use solana_sdk::pubkey::Pubkey;
 
-#[derive(Serialize, Clone)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct HistoricalPriceRaw {
     value: i64,
     scale: u32,
@@ -31,7 +31,7 @@ impl From<&HistoricalPrice> for HistoricalPriceUi {
     }
 }
 
-#[derive(Serialize, Clone)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct OraclePriceHistoryRawData {
     #[serde(with = "pubkey")]
     pub address: Pubkey,
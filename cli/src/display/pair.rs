@@ -1,18 +1,23 @@
 use crate::{
-    display::{mint_decimals, mint_ui_name, u128_ui_amount},
+    display::{
+        decimal_to_str, mint_decimals, mint_sort_key, mint_ui_name, u128_ui_amount, unpriced_mints,
+        usd_value_or_zero, SortBy,
+    },
     pool_vault::{MainVault, MainVaultUiData, SecondaryVault, SecondaryVaultUiData},
     pubkey_str::{pubkey, pubkey_pair},
 };
 use gfx_ssl_v2_interface::{
-    utils::{u128_from_bytes, u16_to_bps},
+    utils::{token_amount, u128_from_bytes, u16_to_bps},
     Pair, PoolRegistry,
 };
-use serde::{self, Serialize};
+use rust_decimal::Decimal;
+use serde::{self, Deserialize, Serialize};
 use solana_client::rpc_client::RpcClient;
 use solana_sdk::pubkey::Pubkey;
+use std::{cmp::Reverse, collections::HashMap};
 
 /// Scale used to record the historical USD volume swapped.
-const USD_VOLUME_DECIMALS: u32 = 6;
+pub const USD_VOLUME_DECIMALS: u32 = 6;
 
 pub struct PairAccountAndVaults {
     pub address: Pubkey,
@@ -59,8 +64,88 @@ impl PairAccountAndVaults {
     }
 }
 
+impl PairAccountAndVaults {
+    /// USD value of the fees generated on both sides of the pair.
+    /// Sides without a price or decimals count as zero.
+    pub fn fees_usd(&self, prices: &HashMap<Pubkey, Decimal>) -> Decimal {
+        let (fees_one, fees_two) = self.pair.total_fees_generated();
+        usd_value_or_zero(fees_one, self.pair.mints.0, prices).saturating_add(usd_value_or_zero(
+            fees_two,
+            self.pair.mints.1,
+            prices,
+        ))
+    }
+
+    /// USD value of the balances of the pair's main vaults.
+    /// Vaults without a price or decimals count as zero.
+    pub fn deposits_usd(&self, prices: &HashMap<Pubkey, Decimal>) -> Decimal {
+        [&self.mint_one_main_vault, &self.mint_two_main_vault]
+            .iter()
+            .fold(Decimal::ZERO, |total, vault| {
+                total.saturating_add(usd_value_or_zero(vault.balance as u128, vault.mint, prices))
+            })
+    }
+}
+
+/// Order pairs for display, valuing fees and deposits in USD with `prices`.
+pub fn sort_pairs(
+    pairs: &mut [PairAccountAndVaults],
+    sort_by: SortBy,
+    prices: &HashMap<Pubkey, Decimal>,
+) {
+    match sort_by {
+        SortBy::Volume => pairs.sort_by_key(|pair| Reverse(pair.pair.historical_volume())),
+        SortBy::Fees => pairs.sort_by_cached_key(|pair| Reverse(pair.fees_usd(prices))),
+        SortBy::Deposits => pairs.sort_by_cached_key(|pair| Reverse(pair.deposits_usd(prices))),
+        SortBy::Mint => pairs.sort_by_cached_key(|pair| {
+            (
+                mint_sort_key(pair.pair.mints.0),
+                mint_sort_key(pair.pair.mints.1),
+            )
+        }),
+    }
+}
+
+/// Totals across pairs, in USD.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct PairsSummary {
+    pub pairs: usize,
+    #[serde(with = "decimal_to_str")]
+    pub total_volume: Decimal,
+    /// According to latest USD oracle prices
+    #[serde(with = "decimal_to_str")]
+    pub total_fees: Decimal,
+    /// Main vault balances, according to latest USD oracle prices
+    #[serde(with = "decimal_to_str")]
+    pub total_deposits: Decimal,
+    /// Mints left out of the fee and deposit totals, for lack of a price or decimals
+    pub unpriced_mints: Vec<String>,
+}
+
+impl PairsSummary {
+    pub fn new(pairs: &[PairAccountAndVaults], prices: &HashMap<Pubkey, Decimal>) -> Self {
+        let mints = pairs
+            .iter()
+            .flat_map(|pair| [pair.pair.mints.0, pair.pair.mints.1]);
+        Self {
+            pairs: pairs.len(),
+            total_volume: pairs.iter().fold(Decimal::ZERO, |total, pair| {
+                let volume = pair.pair.historical_volume();
+                total.saturating_add(token_amount::u128_to_ui(volume, USD_VOLUME_DECIMALS))
+            }),
+            total_fees: pairs.iter().fold(Decimal::ZERO, |total, pair| {
+                total.saturating_add(pair.fees_usd(prices))
+            }),
+            total_deposits: pairs.iter().fold(Decimal::ZERO, |total, pair| {
+                total.saturating_add(pair.deposits_usd(prices))
+            }),
+            unpriced_mints: unpriced_mints(mints, prices),
+        }
+    }
+}
+
 /// Raw data with serde traits, skipping padding and extra space fields
-#[derive(Serialize, Clone)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct PairRawData {
     #[serde(with = "pubkey")]
     address: Pubkey,
@@ -183,3 +268,129 @@ impl From<&PairAccountAndVaults> for PairUiData {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::{BONK_MINT, SOL_MINT, USDC_MINT, USDT_MINT};
+
+    fn pair_with_volume(mints: (Pubkey, Pubkey), volume: u128) -> PairAccountAndVaults {
+        let main_vault = |mint| MainVault {
+            address: Pubkey::new_unique(),
+            mint,
+            mint_decimals: 6,
+            balance: 0,
+        };
+        let secondary_vault = |main_token, mint| SecondaryVault {
+            main_token,
+            mint,
+            address: Pubkey::new_unique(),
+            mint_decimals: 6,
+            balance: 0,
+        };
+        PairAccountAndVaults {
+            address: Pubkey::new_unique(),
+            pair: Pair {
+                mints,
+                total_historical_volume: volume.to_le_bytes(),
+                ..Default::default()
+            },
+            mint_one_main_vault: main_vault(mints.0),
+            mint_one_secondary_vault: secondary_vault(mints.0, mints.1),
+            mint_two_main_vault: main_vault(mints.1),
+            mint_two_secondary_vault: secondary_vault(mints.1, mints.0),
+        }
+    }
+
+    #[test]
+    fn sorts_pairs() {
+        let mut pairs = vec![
+            pair_with_volume((USDC_MINT, USDT_MINT), 5),
+            pair_with_volume((SOL_MINT, USDC_MINT), 20),
+            pair_with_volume((BONK_MINT, SOL_MINT), 10),
+        ];
+        let mints = |pairs: &[PairAccountAndVaults]| {
+            pairs.iter().map(|pair| pair.pair.mints).collect::<Vec<_>>()
+        };
+
+        sort_pairs(&mut pairs, SortBy::Volume, &HashMap::new());
+        assert_eq!(
+            mints(&pairs),
+            vec![
+                (SOL_MINT, USDC_MINT),
+                (BONK_MINT, SOL_MINT),
+                (USDC_MINT, USDT_MINT)
+            ]
+        );
+        sort_pairs(&mut pairs, SortBy::Mint, &HashMap::new());
+        assert_eq!(
+            mints(&pairs),
+            vec![
+                (BONK_MINT, SOL_MINT),
+                (SOL_MINT, USDC_MINT),
+                (USDC_MINT, USDT_MINT)
+            ]
+        );
+    }
+
+    #[test]
+    fn summarizes_pairs() {
+        // $5 and $20 of volume, 1 USDC and 0.5 USDT in fees, 2 USDC in the USDC main vault
+        let mut usdc_usdt = pair_with_volume((USDC_MINT, USDT_MINT), 5_000_000);
+        usdc_usdt.pair.total_fees_generated_native =
+            (1_000_000u128.to_le_bytes(), 500_000u128.to_le_bytes());
+        usdc_usdt.mint_one_main_vault.balance = 2_000_000;
+        let pairs = vec![
+            usdc_usdt,
+            pair_with_volume((SOL_MINT, USDC_MINT), 20_000_000),
+        ];
+        let prices = HashMap::from([(USDC_MINT, Decimal::ONE), (USDT_MINT, Decimal::ONE)]);
+        assert_eq!(
+            PairsSummary::new(&pairs, &prices),
+            PairsSummary {
+                pairs: 2,
+                total_volume: Decimal::from(25),
+                total_fees: Decimal::new(15, 1),
+                total_deposits: Decimal::from(2),
+                unpriced_mints: vec!["SOL".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn raw_data_json_round_trips() {
+        let mints = Pair::normalize_mint_order(Pubkey::new_unique(), Pubkey::new_unique());
+        let pair = Pair {
+            pool_registry: Pubkey::new_unique(),
+            mints,
+            fee_collector: (Pubkey::new_unique(), Pubkey::new_unique()),
+            fee_rates: (10, 20),
+            total_historical_volume: u128::MAX.to_le_bytes(),
+            ..Default::default()
+        };
+        let main_vault = |mint| MainVault {
+            address: Pubkey::new_unique(),
+            mint,
+            mint_decimals: 6,
+            balance: 1_000,
+        };
+        let secondary_vault = |main_token, mint| SecondaryVault {
+            main_token,
+            mint,
+            address: Pubkey::new_unique(),
+            mint_decimals: 6,
+            balance: 2_000,
+        };
+        let raw = PairRawData::from(&PairAccountAndVaults {
+            address: Pubkey::new_unique(),
+            pair,
+            mint_one_main_vault: main_vault(mints.0),
+            mint_one_secondary_vault: secondary_vault(mints.0, mints.1),
+            mint_two_main_vault: main_vault(mints.1),
+            mint_two_secondary_vault: secondary_vault(mints.1, mints.0),
+        });
+        let json = serde_json::to_string(&raw).unwrap();
+        let round_tripped: PairRawData = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string(&round_tripped).unwrap(), json);
+    }
+}
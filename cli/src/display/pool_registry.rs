@@ -3,10 +3,10 @@ use crate::{
     pubkey_str::pubkey,
 };
 use gfx_ssl_v2_interface::MAX_SSL_POOLS_PER_ADMIN;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use solana_sdk::pubkey::Pubkey;
 
-#[derive(Serialize, Clone)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct PoolRegistryRawData {
     #[serde(with = "pubkey")]
     pub admin: Pubkey,
@@ -1,3 +1,4 @@
+use owo_colors::OwoColorize;
 use serde::ser::{self, Serialize, SerializeStruct, Serializer};
 use std::fmt;
 
@@ -15,6 +16,20 @@ where
     serializer.output
 }
 
+/// Like [cli_pretty_print], but with keys and notable values colored
+/// using ANSI escape codes if `color` is set.
+pub fn cli_pretty_print_colored<T>(value: &T, color: bool) -> String
+where
+    T: Serialize,
+{
+    let mut serializer = CliPrettyPrinter {
+        color,
+        ..Default::default()
+    };
+    value.serialize(&mut serializer).unwrap();
+    serializer.output
+}
+
 pub struct CliPrettyPrinter {
     // Output is stored as a String
     output: String,
@@ -22,6 +37,8 @@ pub struct CliPrettyPrinter {
     indent: usize,
     // Indentation string to repeat
     indent_str: String,
+    // Whether to emit ANSI color codes
+    color: bool,
 }
 
 impl Default for CliPrettyPrinter {
@@ -30,6 +47,7 @@ impl Default for CliPrettyPrinter {
             output: Default::default(),
             indent: Default::default(),
             indent_str: DEFAULT_INDENT.to_string(),
+            color: false,
         }
     }
 }
@@ -41,12 +59,38 @@ impl CliPrettyPrinter {
             output: String::new(),
             indent: 0,
             indent_str,
+            color: false,
         }
     }
 
     fn indent(&self) -> String {
         self.indent_str.repeat(self.indent)
     }
+
+    fn key(&self, key: &str) -> String {
+        if self.color {
+            key.cyan().to_string()
+        } else {
+            key.to_string()
+        }
+    }
+
+    /// Separate sequence elements with commas, except after a struct,
+    /// which already ends its own line. Consecutive structs are thereby
+    /// separated by the blank line that starts each struct.
+    fn separate_element(&mut self) {
+        if !self.output.ends_with('[') && !self.output.ends_with('\n') {
+            self.output += ", ";
+        }
+    }
+
+    /// If the last element was a struct, put the closing bracket on its own line.
+    fn close_sequence(&mut self) {
+        if self.output.ends_with('\n') {
+            self.output += &self.indent();
+        }
+        self.output += "]";
+    }
 }
 
 impl<'a> Serializer for &'a mut CliPrettyPrinter {
@@ -154,6 +198,11 @@ impl<'a> Serializer for &'a mut CliPrettyPrinter {
         _variant_index: u32,
         variant: &'static str,
     ) -> Result<()> {
+        // Make suspended pools stand out.
+        if self.color && variant == "Suspended" {
+            self.output += &format!("\"{}\"", variant).red().bold().to_string();
+            return Ok(());
+        }
         self.serialize_str(variant)
     }
 
@@ -174,12 +223,9 @@ impl<'a> Serializer for &'a mut CliPrettyPrinter {
     where
         T: ?Sized + Serialize,
     {
-        self.output += "{";
-        variant.serialize(&mut *self)?;
-        self.output += ":";
-        value.serialize(&mut *self)?;
-        self.output += "}";
-        Ok(())
+        // Labeled like tuple and struct variants, rather than as a JSON object
+        self.output += &format!("{}: ", variant);
+        value.serialize(&mut *self)
     }
 
     fn serialize_struct(self, _name: &'static str, _: usize) -> Result<Self::SerializeStruct> {
@@ -249,7 +295,7 @@ impl<'a> SerializeStruct for &'a mut CliPrettyPrinter {
     where
         T: Serialize,
     {
-        self.output += &format!("{}{}: ", self.indent(), key);
+        self.output += &format!("{}{}: ", self.indent(), self.key(key));
         value.serialize(&mut **self)?;
         if !self.output.ends_with("\n") {
             self.output += "\n";
@@ -308,7 +354,7 @@ impl<'a> ser::SerializeStructVariant for &'a mut CliPrettyPrinter {
     where
         T: ?Sized + Serialize,
     {
-        self.output += &format!("{}{}: ", self.indent(), key);
+        self.output += &format!("{}{}: ", self.indent(), self.key(key));
         value.serialize(&mut **self)?;
         self.output += "\n";
         Ok(())
@@ -328,14 +374,12 @@ impl<'a> ser::SerializeSeq for &'a mut CliPrettyPrinter {
     where
         T: ?Sized + Serialize,
     {
-        if !self.output.ends_with('[') {
-            self.output += ", ";
-        }
+        self.separate_element();
         value.serialize(&mut **self)
     }
 
     fn end(self) -> Result<()> {
-        self.output += "]";
+        self.close_sequence();
         Ok(())
     }
 }
@@ -348,14 +392,12 @@ impl<'a> ser::SerializeTuple for &'a mut CliPrettyPrinter {
     where
         T: ?Sized + Serialize,
     {
-        if !self.output.ends_with('[') {
-            self.output += ", ";
-        }
+        self.separate_element();
         value.serialize(&mut **self)
     }
 
     fn end(self) -> Result<()> {
-        self.output += "]";
+        self.close_sequence();
         Ok(())
     }
 }
@@ -368,14 +410,12 @@ impl<'a> ser::SerializeTupleStruct for &'a mut CliPrettyPrinter {
     where
         T: ?Sized + Serialize,
     {
-        if !self.output.ends_with('[') {
-            self.output += ", ";
-        }
+        self.separate_element();
         value.serialize(&mut **self)
     }
 
     fn end(self) -> Result<()> {
-        self.output += "]";
+        self.close_sequence();
         Ok(())
     }
 }
@@ -388,14 +428,12 @@ impl<'a> ser::SerializeTupleVariant for &'a mut CliPrettyPrinter {
     where
         T: ?Sized + Serialize,
     {
-        if !self.output.ends_with('[') {
-            self.output += ", ";
-        }
+        self.separate_element();
         value.serialize(&mut **self)
     }
 
     fn end(self) -> Result<()> {
-        self.output += "]";
+        self.close_sequence();
         self.indent -= 1;
         Ok(())
     }
@@ -454,4 +492,161 @@ mod tests {
             cli_pretty_print(&my_struct),
         );
     }
+
+    #[test]
+    fn nested_struct_golden() {
+        let my_struct = MyStruct {
+            field1: "Hello".to_string(),
+            field2: MySubStruct {
+                field1: "Foo".to_string(),
+                field2: [8, 9, 10, 11],
+                field3: MyEnum::Variant1("Bar".to_string(), 999),
+                field4: MyEnum::Variant2,
+                field5: MyEnum::Variant3 {
+                    subfield1: 12383.47,
+                    subfield2: "Baz".to_string(),
+                },
+                field6: ("Bang".to_string(), "Boo".to_string()),
+            },
+            field3: -444,
+        };
+        let expected = concat!(
+            "  field1: \"Hello\"\n",
+            "  field2: \n",
+            "    field1: \"Foo\"\n",
+            "    field2: [8, 9, 10, 11]\n",
+            "    field3: Variant1: [\"Bar\", 999]\n",
+            "    field4: \"Variant2\"\n",
+            "    field5: Variant3: \n",
+            "      subfield1: 12383.47\n",
+            "      subfield2: \"Baz\"\n",
+            "    field6: [\"Bang\", \"Boo\"]\n",
+            "  field3: -444\n",
+        );
+        assert_eq!(cli_pretty_print(&my_struct), expected);
+        // The printer is stateless between calls
+        assert_eq!(cli_pretty_print(&my_struct), expected);
+    }
+
+    #[derive(Serialize)]
+    struct WithEmptyValues {
+        items: Vec<u64>,
+        name: Option<String>,
+        amount: Option<String>,
+    }
+
+    #[test]
+    fn empty_sequences_and_none_golden() {
+        let value = WithEmptyValues {
+            items: vec![],
+            name: None,
+            amount: Some("1".to_string()),
+        };
+        let expected = concat!("  items: []\n", "  name: null\n", "  amount: \"1\"\n",);
+        assert_eq!(cli_pretty_print(&value), expected);
+    }
+
+    #[derive(Serialize)]
+    enum Wrapper {
+        Newtype(u64),
+    }
+
+    #[derive(Serialize)]
+    struct WithEnums {
+        tuple: MyEnum,
+        unit: MyEnum,
+        newtype: Wrapper,
+    }
+
+    #[test]
+    fn enums_golden() {
+        let value = WithEnums {
+            tuple: MyEnum::Variant1("A".to_string(), 1),
+            unit: MyEnum::Variant2,
+            newtype: Wrapper::Newtype(5),
+        };
+        let expected = concat!(
+            "  tuple: Variant1: [\"A\", 1]\n",
+            "  unit: \"Variant2\"\n",
+            "  newtype: Newtype: 5\n",
+        );
+        assert_eq!(cli_pretty_print(&value), expected);
+    }
+
+    #[derive(Serialize)]
+    struct Vault {
+        mint: String,
+        balance: u64,
+    }
+
+    #[derive(Serialize)]
+    struct WithStructSequence {
+        name: String,
+        vaults: Vec<Vault>,
+        total: u64,
+    }
+
+    #[test]
+    fn structs_in_sequences_golden() {
+        let value = WithStructSequence {
+            name: "Outer".to_string(),
+            vaults: vec![
+                Vault {
+                    mint: "A".to_string(),
+                    balance: 1,
+                },
+                Vault {
+                    mint: "B".to_string(),
+                    balance: 2,
+                },
+            ],
+            total: 3,
+        };
+        let expected = concat!(
+            "  name: \"Outer\"\n",
+            "  vaults: [\n",
+            "    mint: \"A\"\n",
+            "    balance: 1\n",
+            "\n",
+            "    mint: \"B\"\n",
+            "    balance: 2\n",
+            "  ]\n",
+            "  total: 3\n",
+        );
+        assert_eq!(cli_pretty_print(&value), expected);
+    }
+
+    #[derive(Serialize)]
+    enum Status {
+        Active,
+        Suspended,
+    }
+
+    #[derive(Serialize)]
+    struct Pool {
+        name: String,
+        status: Status,
+    }
+
+    #[test]
+    fn colors_keys_and_suspended_status() {
+        let active = Pool {
+            name: "A".to_string(),
+            status: Status::Active,
+        };
+        assert_eq!(
+            cli_pretty_print_colored(&active, false),
+            cli_pretty_print(&active)
+        );
+        let colored = cli_pretty_print_colored(&active, true);
+        assert!(colored.contains(&format!("{}: \"A\"", "name".cyan())));
+        assert!(colored.contains(&format!("{}: \"Active\"", "status".cyan())));
+
+        let suspended = Pool {
+            name: "B".to_string(),
+            status: Status::Suspended,
+        };
+        let colored = cli_pretty_print_colored(&suspended, true);
+        assert!(colored.contains(&"\"Suspended\"".red().bold().to_string()));
+    }
 }
@@ -1,20 +1,27 @@
 use crate::{
     display::{
+        decimal_to_str,
         math_params::{SSLMathParamsRawData, SSLMathParamsUiData},
-        mint_ui_name, ui_amount,
+        mint_ui_name, ui_amount, value_in_usd,
     },
     pool_vault::{MainVault, MainVaultUiData, SecondaryVault, SecondaryVaultUiData},
     pubkey_str::{pubkey, pubkey_array},
 };
 use gfx_ssl_v2_interface::{
-    ssl_pool::MAX_NUM_ORACLES_PER_MINT, utils::token_amount, AssetType, PoolRegistry, SSLPool,
-    SSLPoolStatus,
+    ssl_pool::MAX_NUM_ORACLES_PER_MINT, utils::token_amount, AssetType, OraclePriceHistory,
+    PoolRegistry, SSLPool, SSLPoolStatus,
 };
 use rust_decimal::Decimal;
-use serde::{self, Serialize};
+use serde::{self, Deserialize, Serialize};
 use solana_client::rpc_client::RpcClient;
 use solana_sdk::pubkey::Pubkey;
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    fmt::{Display, Formatter},
+    fs::OpenOptions,
+    io::Write,
+    path::Path,
+};
 
 pub struct SSLPoolData {
     pub pool: SSLPool,
@@ -35,7 +42,7 @@ impl SSLPoolData {
         let other_pools: Vec<SSLPool> = pool_registry
             .entries
             .into_iter()
-            .filter(|other_pool| *other_pool != SSLPool::default())
+            .filter(|other_pool| !other_pool.is_empty())
             .filter(|other_pool| other_pool.mint != pool.mint)
             .collect();
         let secondary_vaults: Vec<SecondaryVault> = other_pools
@@ -59,7 +66,7 @@ impl SSLPoolData {
 }
 
 /// Raw data with serde traits, skipping padding and extra space fields
-#[derive(Serialize, Clone)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct SSLPoolRawData {
     pub status: u8,
     pub asset_type: u8,
@@ -163,75 +170,187 @@ pub struct MarketMakingReport {
     /// According to latest USD oracle price
     #[serde(with = "decimal_to_str")]
     pub liquidity_deposits_value: Decimal,
+    /// Total LP rewards accumulated by the pool, in the main token.
+    /// Only written to CSV, so the console output is unaffected.
+    #[serde(skip)]
+    pub lp_rewards: Decimal,
+    /// Main vault value plus secondary vault holdings
     #[serde(with = "decimal_to_str")]
     pub total_pool_value: Decimal,
+    /// Total pool value less the USD value of liquidity deposits
     #[serde(with = "decimal_to_str")]
     pub market_pnl: Decimal,
     #[serde(with = "decimal_to_str")]
     pub market_pnl_pct: Decimal,
+    /// USD value of simply holding the deposited main tokens,
+    /// less the current total pool value. Positive values are a loss.
+    #[serde(with = "decimal_to_str")]
+    pub impermanent_loss_usd: Decimal,
+    #[serde(with = "decimal_to_str")]
+    pub impermanent_loss_pct: Decimal,
     #[serde(with = "decimal_to_str")]
     pub main_vault_balance: Decimal,
     #[serde(with = "decimal_to_str")]
     pub main_vault_value: Decimal,
     #[serde(with = "decimal_to_str")]
     pub main_vault_value_pct: Decimal,
+    /// Secondary tokens acquired through swaps,
+    /// valued at their latest USD oracle prices
     #[serde(with = "decimal_to_str")]
-    pub total_secondary_vault_value: Decimal,
+    pub secondary_holdings_usd: Decimal,
     #[serde(with = "decimal_to_str")]
-    pub total_secondary_vault_value_pct: Decimal,
+    pub secondary_holdings_pct: Decimal,
     pub secondary_vaults: Vec<SecondaryVaultValuation>,
 }
 
+/// Latest USD price of each mint in the pool registry. Mints whose price history
+/// can't be fetched, or has never been cranked, are left out of the map.
+pub fn latest_prices<F>(
+    pool_registry: &PoolRegistry,
+    get_price_history: F,
+) -> HashMap<Pubkey, Decimal>
+where
+    F: Fn(&Pubkey) -> Option<OraclePriceHistory>,
+{
+    pool_registry
+        .entries
+        .iter()
+        .filter(|pool| pool.is_initialized())
+        .filter_map(|pool| {
+            let price_history = get_price_history(&pool.oracle_price_histories[0])?;
+            let latest_price = price_history.latest_price().ok()?;
+            Some((pool.mint, latest_price.price.into()))
+        })
+        .collect()
+}
+
+/// A pool could not be valued because the latest price of one of its mints is unavailable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PriceUnavailable {
+    pub pool_mint: Pubkey,
+    pub mint: Pubkey,
+}
+
+impl Display for PriceUnavailable {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Pool {}: price unavailable for mint {}",
+            self.pool_mint, self.mint
+        )
+    }
+}
+
 impl MarketMakingReport {
     pub fn generate(
-        total_liquidity_deposits: Decimal,
         pool_accounts_and_data: SSLPoolData,
         latest_prices: &HashMap<Pubkey, Decimal>,
-    ) -> Self {
+    ) -> Result<Self, PriceUnavailable> {
+        let pool_mint = pool_accounts_and_data.pool.mint;
+        let get_price = |mint: &Pubkey| {
+            latest_prices.get(mint).ok_or(PriceUnavailable {
+                pool_mint,
+                mint: *mint,
+            })
+        };
+        let decimals = pool_accounts_and_data.pool.mint_decimals as u32;
+        let total_liquidity_deposits = pool_accounts_and_data.pool.total_liquidity_deposits;
+        let lp_rewards = token_amount::to_ui(
+            pool_accounts_and_data.pool.total_accumulated_lp_reward,
+            decimals,
+        );
         let main_vault = pool_accounts_and_data.main_vault.unwrap();
         let main_vault_balance = token_amount::to_ui(main_vault.balance, main_vault.mint_decimals);
-        let token_price = latest_prices.get(&main_vault.mint).unwrap();
-        let liquidity_deposits_value = total_liquidity_deposits * token_price;
-        let main_vault_value = main_vault_balance * token_price;
-        let mut total_secondary_vault_value = Decimal::ZERO;
+        let token_price = get_price(&main_vault.mint)?;
+        let liquidity_deposits_value =
+            value_in_usd(total_liquidity_deposits, decimals, *token_price);
+        let main_vault_value =
+            value_in_usd(main_vault.balance, main_vault.mint_decimals, *token_price);
+        let mut secondary_holdings_usd = Decimal::ZERO;
         let mut secondary_vaults = pool_accounts_and_data
             .secondary_vaults
             .into_iter()
             .map(|vault| {
-                let secondary_token_price = latest_prices.get(&vault.mint).unwrap();
+                let secondary_token_price = get_price(&vault.mint)?;
                 let balance = token_amount::to_ui(vault.balance, vault.mint_decimals);
-                let value = balance * secondary_token_price;
-                total_secondary_vault_value += value;
-                SecondaryVaultValuation {
+                let value =
+                    value_in_usd(vault.balance, vault.mint_decimals, *secondary_token_price);
+                secondary_holdings_usd += value;
+                Ok(SecondaryVaultValuation {
                     mint: vault.mint,
                     balance,
                     value,
                     value_pct: Decimal::ZERO,
-                }
+                })
             })
-            .collect::<Vec<SecondaryVaultValuation>>();
-        let total_pool_value = main_vault_value + total_secondary_vault_value;
-        let main_vault_value_pct = main_vault_value / total_pool_value;
+            .collect::<Result<Vec<SecondaryVaultValuation>, PriceUnavailable>>()?;
+        let total_pool_value = main_vault_value + secondary_holdings_usd;
+        let main_vault_value_pct = ratio_or_zero(main_vault_value, total_pool_value);
         secondary_vaults
             .iter_mut()
-            .for_each(|vault| vault.value_pct = vault.value / total_pool_value);
-        let total_secondary_vault_value_pct = total_secondary_vault_value / total_pool_value;
+            .for_each(|vault| vault.value_pct = ratio_or_zero(vault.value, total_pool_value));
+        let secondary_holdings_pct = ratio_or_zero(secondary_holdings_usd, total_pool_value);
         let market_pnl = total_pool_value - liquidity_deposits_value;
-        let market_pnl_pct = market_pnl / liquidity_deposits_value;
-        Self {
-            mint: pool_accounts_and_data.pool.mint,
-            liquidity_deposits: total_liquidity_deposits,
+        let market_pnl_pct = ratio_or_zero(market_pnl, liquidity_deposits_value);
+        let impermanent_loss_usd = liquidity_deposits_value - total_pool_value;
+        let impermanent_loss_pct = ratio_or_zero(impermanent_loss_usd, liquidity_deposits_value);
+        Ok(Self {
+            mint: pool_mint,
+            liquidity_deposits: token_amount::to_ui(total_liquidity_deposits, decimals),
             liquidity_deposits_value,
+            lp_rewards,
             main_vault_balance,
             main_vault_value,
             main_vault_value_pct,
             secondary_vaults,
-            total_secondary_vault_value,
-            total_secondary_vault_value_pct,
+            secondary_holdings_usd,
+            secondary_holdings_pct,
             total_pool_value,
             market_pnl,
             market_pnl_pct,
+            impermanent_loss_usd,
+            impermanent_loss_pct,
+        })
+    }
+}
+
+/// `numerator / denominator`, or zero for an empty pool, where the denominator is zero.
+fn ratio_or_zero(numerator: Decimal, denominator: Decimal) -> Decimal {
+    numerator.checked_div(denominator).unwrap_or(Decimal::ZERO)
+}
+
+impl MarketMakingReport {
+    pub const CSV_HEADER: &'static str = "timestamp,mint,liquidity_deposits,lp_rewards,\
+        market_pnl,market_pnl_pct,total_pool_value,secondary_holdings_usd,\
+        impermanent_loss_usd,impermanent_loss_pct";
+
+    pub fn to_csv_row(&self, timestamp: &str) -> String {
+        format!(
+            "{},{},{},{},{},{},{},{},{},{}",
+            timestamp,
+            self.mint,
+            self.liquidity_deposits,
+            self.lp_rewards,
+            self.market_pnl,
+            self.market_pnl_pct,
+            self.total_pool_value,
+            self.secondary_holdings_usd,
+            self.impermanent_loss_usd,
+            self.impermanent_loss_pct,
+        )
+    }
+
+    /// Append one row per report to the CSV file at `path`,
+    /// creating the file with a header if it is absent or empty.
+    pub fn append_csv(reports: &[Self], timestamp: &str, path: &Path) -> std::io::Result<()> {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        if file.metadata()?.len() == 0 {
+            writeln!(file, "{}", Self::CSV_HEADER)?;
         }
+        for report in reports {
+            writeln!(file, "{}", report.to_csv_row(timestamp))?;
+        }
+        Ok(())
     }
 }
 
@@ -241,15 +360,206 @@ impl From<&MarketMakingReport> for MarketMakingReport {
     }
 }
 
-mod decimal_to_str {
-    use rust_decimal::Decimal;
-    use serde::{self, Serializer};
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gfx_ssl_v2_interface::{HistoricalDecimal, HistoricalPrice, SSLMathParams};
 
-    pub fn serialize<S>(decimal: &Decimal, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: Serializer,
-    {
-        let s = format!("{}", decimal);
-        serializer.serialize_str(&s)
+    #[test]
+    fn raw_data_json_round_trips() {
+        let mint = Pubkey::new_unique();
+        let pool = SSLPool::new(
+            SSLPoolStatus::Active,
+            AssetType::Stable,
+            mint,
+            6,
+            0,
+            Pubkey::new_unique(),
+            0,
+            SSLMathParams::default(),
+        );
+        let raw = SSLPoolRawData::from(&SSLPoolData {
+            pool,
+            main_vault: Some(MainVault {
+                address: Pubkey::new_unique(),
+                mint,
+                mint_decimals: 6,
+                balance: 1_000,
+            }),
+            secondary_vaults: vec![SecondaryVault {
+                main_token: mint,
+                mint: Pubkey::new_unique(),
+                address: Pubkey::new_unique(),
+                mint_decimals: 9,
+                balance: 2_000,
+            }],
+        });
+        let json = serde_json::to_string(&raw).unwrap();
+        let round_tripped: SSLPoolRawData = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string(&round_tripped).unwrap(), json);
+    }
+
+    #[test]
+    fn report_on_empty_pool_has_zero_ratios() {
+        let mint = Pubkey::new_unique();
+        let secondary_mint = Pubkey::new_unique();
+        let pool = SSLPool::new(
+            SSLPoolStatus::Active,
+            AssetType::Stable,
+            mint,
+            6,
+            0,
+            Pubkey::new_unique(),
+            0,
+            SSLMathParams::default(),
+        );
+        let pool_data = SSLPoolData {
+            pool,
+            main_vault: Some(MainVault {
+                address: Pubkey::new_unique(),
+                mint,
+                mint_decimals: 6,
+                balance: 0,
+            }),
+            secondary_vaults: vec![SecondaryVault {
+                main_token: mint,
+                mint: secondary_mint,
+                address: Pubkey::new_unique(),
+                mint_decimals: 9,
+                balance: 0,
+            }],
+        };
+        let prices = HashMap::from([(mint, Decimal::ONE), (secondary_mint, Decimal::from(20))]);
+        let report = MarketMakingReport::generate(pool_data, &prices).unwrap();
+        assert_eq!(report.total_pool_value, Decimal::ZERO);
+        assert_eq!(report.main_vault_value_pct, Decimal::ZERO);
+        assert_eq!(report.secondary_vaults[0].value_pct, Decimal::ZERO);
+        assert_eq!(report.secondary_holdings_pct, Decimal::ZERO);
+        assert_eq!(report.market_pnl, Decimal::ZERO);
+        assert_eq!(report.market_pnl_pct, Decimal::ZERO);
+        assert_eq!(report.impermanent_loss_usd, Decimal::ZERO);
+        assert_eq!(report.impermanent_loss_pct, Decimal::ZERO);
+    }
+
+    #[test]
+    fn report_values_secondary_holdings() {
+        let mint = Pubkey::new_unique();
+        let secondary_mint = Pubkey::new_unique();
+        let pool = SSLPool::new(
+            SSLPoolStatus::Active,
+            AssetType::Stable,
+            mint,
+            6,
+            0,
+            Pubkey::new_unique(),
+            100_000_000,
+            SSLMathParams::default(),
+        );
+        // 80 of the 100 deposited tokens were swapped out for 1 secondary token
+        let pool_data = SSLPoolData {
+            pool,
+            main_vault: Some(MainVault {
+                address: Pubkey::new_unique(),
+                mint,
+                mint_decimals: 6,
+                balance: 80_000_000,
+            }),
+            secondary_vaults: vec![SecondaryVault {
+                main_token: mint,
+                mint: secondary_mint,
+                address: Pubkey::new_unique(),
+                mint_decimals: 9,
+                balance: 1_000_000_000,
+            }],
+        };
+        let prices = HashMap::from([(mint, Decimal::ONE), (secondary_mint, Decimal::from(15))]);
+        let report = MarketMakingReport::generate(pool_data, &prices).unwrap();
+        assert_eq!(report.liquidity_deposits_value, Decimal::from(100));
+        assert_eq!(report.secondary_holdings_usd, Decimal::from(15));
+        assert_eq!(report.total_pool_value, Decimal::from(95));
+        assert_eq!(report.market_pnl, Decimal::from(-5));
+        assert_eq!(report.market_pnl_pct, Decimal::new(-5, 2));
+        assert_eq!(report.impermanent_loss_usd, Decimal::from(5));
+        assert_eq!(report.impermanent_loss_pct, Decimal::new(5, 2));
+    }
+
+    #[test]
+    fn csv_header_is_written_once() {
+        let report = |mint| MarketMakingReport {
+            mint,
+            liquidity_deposits: Decimal::from(100),
+            liquidity_deposits_value: Decimal::from(100),
+            lp_rewards: Decimal::new(15, 1),
+            total_pool_value: Decimal::from(110),
+            market_pnl: Decimal::from(10),
+            market_pnl_pct: Decimal::new(1, 1),
+            impermanent_loss_usd: Decimal::from(-10),
+            impermanent_loss_pct: Decimal::new(-1, 1),
+            main_vault_balance: Decimal::from(90),
+            main_vault_value: Decimal::from(90),
+            main_vault_value_pct: Decimal::new(818, 3),
+            secondary_holdings_usd: Decimal::from(20),
+            secondary_holdings_pct: Decimal::new(182, 3),
+            secondary_vaults: vec![],
+        };
+        let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let path = std::env::temp_dir().join(format!("market-making-pnl-{}.csv", mint_a));
+        MarketMakingReport::append_csv(&[report(mint_a)], "t0", &path).unwrap();
+        MarketMakingReport::append_csv(&[report(mint_a), report(mint_b)], "t1", &path).unwrap();
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let row =
+            |timestamp, mint| format!("{},{},100,1.5,10,0.1,110,20,-10,-0.1", timestamp, mint);
+        assert_eq!(
+            contents.lines().collect::<Vec<_>>(),
+            vec![
+                MarketMakingReport::CSV_HEADER.to_string(),
+                row("t0", mint_a),
+                row("t1", mint_a),
+                row("t1", mint_b),
+            ]
+        );
+
+        // Console and JSON output don't include the CSV-only field
+        let json = serde_json::to_value(report(mint_a)).unwrap();
+        assert!(json.get("lp_rewards").is_none());
+        // and name secondary holdings the same as the CSV column
+        assert_eq!(json["secondary_holdings_usd"], "20");
+        assert_eq!(json["secondary_holdings_pct"], "0.182");
+        assert_eq!(json["impermanent_loss_usd"], "-10");
+        assert_eq!(json["impermanent_loss_pct"], "-0.1");
+    }
+
+    #[test]
+    fn latest_prices_skips_empty_price_histories() {
+        let mut pool_registry = PoolRegistry::default();
+        let mints = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let price_histories = [Pubkey::new_unique(), Pubkey::new_unique()];
+        for idx in 0..2 {
+            pool_registry.entries[idx] = SSLPool::new(
+                SSLPoolStatus::Active,
+                AssetType::Stable,
+                mints[idx],
+                6,
+                0,
+                price_histories[idx],
+                0,
+                SSLMathParams::default(),
+            );
+        }
+        pool_registry.num_entries = 2;
+        let prices = latest_prices(&pool_registry, |address| {
+            let mut price_history = OraclePriceHistory::default();
+            if *address == price_histories[0] {
+                price_history.push(HistoricalPrice {
+                    price: HistoricalDecimal::new(101, 2),
+                    slot: 1,
+                });
+            }
+            Some(price_history)
+        });
+        assert_eq!(prices.len(), 1);
+        assert_eq!(prices.get(&mints[0]), Some(&Decimal::new(101, 2)));
+        assert_eq!(prices.get(&mints[1]), None);
     }
 }
@@ -1,35 +1,82 @@
 pub mod display;
+pub mod liquidity_history;
 pub mod pool_vault;
 pub mod pubkey_str;
+pub mod rate_limit;
 mod ssl_types;
 
 use crate::{
     display::{
-        cli_display,
-        liquidity_account::{LiquidityAccountRawData, LiquidityAccountUiData},
+        cache_mint_decimals, cli_display, cli_display_with_summary, estimate_seconds_from_slots,
+        liquidity_account::{
+            estimated_claim, sort_liquidity_accounts, LiquidityAccountRawData,
+            LiquidityAccountUiData, LiquidityAccountsSummary,
+        },
+        mint_decimals_or_fetch,
         oracle_price_history::{OraclePriceHistoryRawData, OraclePriceHistoryUiData},
-        pair::{PairAccountAndVaults, PairRawData, PairUiData},
-        ssl_pool::{MarketMakingReport, SSLPoolData, SSLPoolRawData, SSLPoolUiData},
+        pair::{
+            sort_pairs, PairAccountAndVaults, PairRawData, PairUiData, PairsSummary,
+            USD_VOLUME_DECIMALS,
+        },
+        ssl_pool::{latest_prices, MarketMakingReport, SSLPoolData, SSLPoolRawData, SSLPoolUiData},
+        ColorChoice, SortBy, MAINNET_POOL_REGISTRY,
     },
+    liquidity_history::fetch_liquidity_history,
+    rate_limit::RateLimitedSender,
     ssl_types::PoolRegistryConfig,
 };
 use anchor_lang::AccountDeserialize;
 use anchor_spl::{associated_token::get_associated_token_address, token::Mint};
 use anyhow::anyhow;
+use chrono::Utc;
 use clap::{IntoApp, Parser};
 use gfx_ssl_v2_interface::{
-    utils::token_amount, LiquidityAccount, OraclePriceHistory, Pair, PoolRegistry, SSLMathConfig,
-    SSLPool,
+    token_ratio_category, utils::token_amount, AssetType, EventEmitter, LiquidityAccount,
+    OraclePriceHistory, Pair, PoolRegistry, SSLMathConfig, SSLPool, SSLPoolStatus,
+};
+use gfx_ssl_v2_sdk::{
+    discriminator::{account_type_for_discriminator, AccountKind},
+    instructions::*,
+    lookup_table::*,
+    oracle::{check_price_bounds, oracle_price, price_impact_bps, verify_oracle_account},
+    state::*,
 };
-use gfx_ssl_v2_sdk::{instructions::*, state::*};
 use rust_decimal::Decimal;
-use solana_client::rpc_client::RpcClient;
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind},
+    rpc_client::{RpcClient, RpcClientConfig, SerializableTransaction},
+    rpc_config::RpcSimulateTransactionConfig,
+};
 use solana_devtools_cli_config::{CommitmentArg, KeypairArg, UrlArg};
 use solana_sdk::{
-    compute_budget::ComputeBudgetInstruction, instruction::Instruction, message::Message, pubkey,
-    pubkey::Pubkey, transaction::Transaction,
+    address_lookup_table_account::AddressLookupTableAccount,
+    compute_budget::ComputeBudgetInstruction,
+    hash::Hash,
+    instruction::Instruction,
+    message::{v0, Message, VersionedMessage},
+    pubkey,
+    pubkey::Pubkey,
+    signature::Signature,
+    signer::signers::Signers,
+    transaction::{Transaction, TransactionError, VersionedTransaction},
 };
-use std::{collections::HashMap, fs};
+use std::{
+    collections::HashMap,
+    fs,
+    num::NonZeroU32,
+    path::PathBuf,
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Wait time before the first retry of a failed transaction, doubled on every retry.
+const INITIAL_RETRY_BACKOFF: Duration = Duration::from_millis(500);
+/// Upper bound on the wait time between retries.
+const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(8);
+/// How often to poll for the status of a sent transaction.
+const CONFIRMATION_POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// Roughly the lifetime of a blockhash.
+const CONFIRMATION_TIMEOUT: Duration = Duration::from_secs(90);
 
 #[derive(Parser, Debug)]
 pub enum Subcommand {
@@ -51,6 +98,10 @@ pub enum Subcommand {
         /// encoded transaction message, useful for multisig proposals.
         #[clap(long)]
         print_only: bool,
+        /// Print the fields that would change, compared to the current on-chain values,
+        /// instead of sending a transaction.
+        #[clap(long)]
+        diff: bool,
         #[clap(parse(try_from_str=Pubkey::try_from))]
         pool_registry: Pubkey,
         /// Path to a JSON file containing the mathematical parameters
@@ -68,6 +119,12 @@ pub enum Subcommand {
         /// Path to a JSON file containing the mathematical parameters
         /// used for price calculation.
         json_params_path: String,
+        /// Refuse to create the pool if the oracle's current price is below this value.
+        #[clap(long)]
+        min_price: Option<Decimal>,
+        /// Refuse to create the pool if the oracle's current price is above this value.
+        #[clap(long)]
+        max_price: Option<Decimal>,
     },
     /// Configure the parameters of an SSL pool.
     ConfigSsl {
@@ -75,6 +132,10 @@ pub enum Subcommand {
         /// encoded transaction message, useful for multisig proposals.
         #[clap(long)]
         print_only: bool,
+        /// Print the fields that would change, compared to the current on-chain values,
+        /// instead of sending a transaction.
+        #[clap(long)]
+        diff: bool,
         /// The pool registry which hosts the SSL pool being configured.
         #[clap(long, parse(try_from_str=Pubkey::try_from))]
         pool_registry: Pubkey,
@@ -150,7 +211,8 @@ pub enum Subcommand {
         /// The pair to configure
         #[clap(long, parse(try_from_str=Pubkey::try_from))]
         pair: Pubkey,
-        /// The mint whose parameters to reconfigure
+        /// The mint whose parameters to reconfigure.
+        /// The fee rate and destination apply to swaps that output this mint.
         #[clap(long, parse(try_from_str=Pubkey::try_from))]
         mint: Pubkey,
         /// Configures the pair to a new fee destination.
@@ -221,6 +283,13 @@ pub enum Subcommand {
         /// Fees are claimed in this mint.
         #[clap(parse(try_from_str=Pubkey::try_from))]
         mint: Pubkey,
+        /// The owner of the liquidity account. The program requires the owner to sign,
+        /// so this is only accepted if it matches the signer.
+        #[clap(long, parse(try_from_str=Pubkey::try_from))]
+        owner: Option<Pubkey>,
+        /// Send the claim even if the estimated claimable fees are zero.
+        #[clap(long)]
+        force: bool,
     },
     /// User instruction to create a liquidity account for a given SSL pool
     /// as specified by pool registry and mint.
@@ -232,6 +301,10 @@ pub enum Subcommand {
         /// Specifies the SSL pool for this mint.
         #[clap(parse(try_from_str=Pubkey::try_from))]
         mint: Pubkey,
+        /// The owner of the liquidity account. The program requires the owner to sign,
+        /// so this is only accepted if it matches the signer.
+        #[clap(long, parse(try_from_str=Pubkey::try_from))]
+        owner: Option<Pubkey>,
     },
     /// User instruction to close a liquidity account.
     /// Liquidity deposit must be zero.
@@ -257,7 +330,20 @@ pub enum Subcommand {
         #[clap(parse(try_from_str=Pubkey::try_from))]
         mint: Pubkey,
         /// Native token amount (i.e. satoshis, lamports, etc) to deposit.
-        amount: u64,
+        #[clap(required_unless_present = "ui_amount")]
+        amount: Option<u64>,
+        /// UI token amount to deposit, converted using the mint's decimals.
+        /// Alternative to the native `amount`.
+        #[clap(long, conflicts_with = "amount")]
+        ui_amount: Option<Decimal>,
+        /// Create the signer's liquidity account in the same transaction
+        /// if it doesn't exist yet.
+        #[clap(long)]
+        create_if_missing: bool,
+        /// The owner of the liquidity account. The program requires the owner to sign,
+        /// so this is only accepted if it matches the signer.
+        #[clap(long, parse(try_from_str=Pubkey::try_from))]
+        owner: Option<Pubkey>,
     },
     /// User instruction to withdraw liquidity from a pool
     /// as specified by mint.
@@ -269,7 +355,16 @@ pub enum Subcommand {
         #[clap(parse(try_from_str=Pubkey::try_from))]
         mint: Pubkey,
         /// Native token amount (i.e. satoshis, lamports, etc) to withdraw.
-        amount: u64,
+        #[clap(required_unless_present = "ui_amount")]
+        amount: Option<u64>,
+        /// UI token amount to withdraw, converted using the mint's decimals.
+        /// Alternative to the native `amount`.
+        #[clap(long, conflicts_with = "amount")]
+        ui_amount: Option<Decimal>,
+        /// The owner of the liquidity account. The program requires the owner to sign,
+        /// so this is only accepted if it matches the signer.
+        #[clap(long, parse(try_from_str=Pubkey::try_from))]
+        owner: Option<Pubkey>,
     },
     /// User swap instruction.
     /// Does not require that the user has a liquidity account.
@@ -277,8 +372,14 @@ pub enum Subcommand {
     Swap {
         /// Fail the instruction if the user receives less than this
         /// native amount (i.e. satoshies, lamports, etc) out.
+        /// When set, the swap is quoted in a simulation first,
+        /// and isn't sent if the quote is below this amount.
         #[clap(long, default_value_t = 0)]
         min_out: u64,
+        /// Don't send the swap if its quoted price impact, relative to
+        /// the current oracle prices, exceeds this many basis points.
+        #[clap(long)]
+        max_price_impact_bps: Option<u64>,
         /// The mint that the user is relinquishing.
         #[clap(long = "in", parse(try_from_str=Pubkey::try_from))]
         mint_in: Pubkey,
@@ -286,12 +387,34 @@ pub enum Subcommand {
         #[clap(long = "out", parse(try_from_str=Pubkey::try_from))]
         mint_out: Pubkey,
         /// The pool registry that hosts the SSL pools used in the swap.
+        /// Defaults to `--default-registry`.
         #[clap(long, parse(try_from_str=Pubkey::try_from))]
-        pool_registry: Pubkey,
+        pool_registry: Option<Pubkey>,
         /// Native token amount (i.e. satoshis, lamports, etc) that the user
         /// is relinquishing. User must have at least this amount in their
         /// associated token account for `mint-in`.
-        amount_in: u64,
+        #[clap(required_unless_present = "ui_amount")]
+        amount_in: Option<u64>,
+        /// UI token amount that the user is relinquishing, converted using
+        /// the decimals of `mint-in`. Alternative to the native `amount-in`.
+        #[clap(long, conflicts_with = "amount_in")]
+        ui_amount: Option<Decimal>,
+        /// Send a v0 transaction using this address lookup table,
+        /// e.g. one created with `create-pair-lookup-table`.
+        #[clap(long, parse(try_from_str=Pubkey::try_from))]
+        use_lut: Option<Pubkey>,
+    },
+    /// Create an address lookup table containing every account used by swaps on a pair,
+    /// except for the user's accounts. The signer is the lookup table's authority.
+    CreatePairLookupTable {
+        /// The pool registry that hosts the pair.
+        /// Defaults to `--default-registry`.
+        #[clap(long, parse(try_from_str=Pubkey::try_from))]
+        pool_registry: Option<Pubkey>,
+        #[clap(long, parse(try_from_str=Pubkey::try_from))]
+        mint_one: Pubkey,
+        #[clap(long, parse(try_from_str=Pubkey::try_from))]
+        mint_two: Pubkey,
     },
     /// Print the address for a pool registry PDA.
     GetPoolRegistryAddress {
@@ -369,6 +492,12 @@ pub enum Subcommand {
         #[clap(long, parse(try_from_str=Pubkey::try_from))]
         owner: Option<Pubkey>,
     },
+    /// List every pool registry on the cluster, with its admin and number of SSL pools.
+    ListRegistries {
+        /// Display the data in JSON format
+        #[clap(long)]
+        json: bool,
+    },
     /// Display the account data on a pool registry.
     GetPoolRegistry {
         /// Display the fields without any UI formatting
@@ -397,6 +526,20 @@ pub enum Subcommand {
         #[clap(parse(try_from_str=Pubkey::try_from))]
         mint: Pubkey,
     },
+    /// Export the current configuration of an SSL pool as JSON,
+    /// in the format accepted by `create-ssl`.
+    GetConfig {
+        /// The pool registry address
+        /// Defaults to `--default-registry`.
+        #[clap(long, parse(try_from_str=Pubkey::try_from))]
+        pool_registry: Option<Pubkey>,
+        /// Identifies the SSL pool by its main token.
+        #[clap(long, parse(try_from_str=Pubkey::try_from))]
+        mint: Pubkey,
+        /// Write the JSON to this file instead of printing it.
+        #[clap(long)]
+        out: Option<PathBuf>,
+    },
     /// Display the account data for a Pair account.
     GetPair {
         /// Display the fields without any UI formatting
@@ -426,6 +569,15 @@ pub enum Subcommand {
         /// Display the data in JSON format
         #[clap(long)]
         json: bool,
+        /// Order the records, valuing fees and deposits at the latest oracle prices
+        #[clap(long, arg_enum)]
+        sort_by: Option<SortBy>,
+        /// Follow the records with USD totals. On by default, except for JSON output
+        #[clap(long)]
+        summary: bool,
+        /// Don't follow the records with USD totals
+        #[clap(long, conflicts_with = "summary")]
+        no_summary: bool,
     },
     /// Display the account data for an oracle price history account.
     GetOraclePriceHistory {
@@ -458,6 +610,11 @@ pub enum Subcommand {
         /// Defaults to the -k/--keypair argument or Solana CLI configured signer.
         #[clap(long, parse(try_from_str=Pubkey::try_from))]
         owner: Option<Pubkey>,
+        /// Also print the deposits, withdrawals and fee claims found in the
+        /// liquidity account's most recent transactions, oldest first.
+        /// Only instructions sent directly to the SSL program are found, not CPIs.
+        #[clap(long)]
+        history: bool,
     },
     /// Get all of a given owner's liquidity accounts
     GetLiquidityAccounts {
@@ -474,6 +631,26 @@ pub enum Subcommand {
         /// Display the data in JSON format
         #[clap(long)]
         json: bool,
+        /// Order the records, valuing fees and deposits at the latest oracle prices
+        #[clap(long, arg_enum)]
+        sort_by: Option<SortBy>,
+        /// Follow the records with USD totals. On by default, except for JSON output
+        #[clap(long)]
+        summary: bool,
+        /// Don't follow the records with USD totals
+        #[clap(long, conflicts_with = "summary")]
+        no_summary: bool,
+    },
+    /// Display any GFX SSLv2 account, detecting its type from the account discriminator.
+    DecodeAccount {
+        /// Display the fields without any UI formatting
+        #[clap(long)]
+        raw: bool,
+        /// Display the data in JSON format
+        #[clap(long)]
+        json: bool,
+        #[clap(parse(try_from_str=Pubkey::try_from))]
+        address: Pubkey,
     },
     MarketMakingPnl {
         /// The pool registry address
@@ -485,6 +662,46 @@ pub enum Subcommand {
         /// Display the data in JSON format
         #[clap(long)]
         json: bool,
+        /// Append one timestamped row per pool to this CSV file,
+        /// creating it with a header if absent.
+        #[clap(long)]
+        csv_out: Option<PathBuf>,
+    },
+    /// Continually sample a pair's lifetime volume and fee counters,
+    /// and print how much they increased over each interval.
+    PairVolume {
+        /// The pool registry that hosts the pair.
+        /// Defaults to `--default-registry`.
+        #[clap(long, parse(try_from_str=Pubkey::try_from))]
+        pool_registry: Option<Pubkey>,
+        #[clap(long, parse(try_from_str=Pubkey::try_from))]
+        mint_one: Pubkey,
+        #[clap(long, parse(try_from_str=Pubkey::try_from))]
+        mint_two: Pubkey,
+        /// Milliseconds between samples.
+        #[clap(long, default_value_t = 10_000)]
+        interval_ms: u64,
+    },
+    /// Explain how a swap would be priced: the oracle prices of both mints,
+    /// the Bollinger band of their price ratio, the fee rate,
+    /// and a simulated quote. Nothing is sent.
+    GetSwapRoute {
+        /// The mint that the user would relinquish.
+        #[clap(long = "in", parse(try_from_str=Pubkey::try_from))]
+        mint_in: Pubkey,
+        /// The mint that the user would receive.
+        #[clap(long = "out", parse(try_from_str=Pubkey::try_from))]
+        mint_out: Pubkey,
+        /// The pool registry that hosts the SSL pools used in the swap.
+        /// Defaults to `--default-registry`.
+        #[clap(long, parse(try_from_str=Pubkey::try_from))]
+        pool_registry: Option<Pubkey>,
+        /// Native token amount (i.e. satoshis, lamports, etc) of `mint-in` to quote.
+        #[clap(long, required_unless_present = "ui_amount")]
+        amount: Option<u64>,
+        /// UI token amount of `mint-in` to quote, converted using its decimals.
+        #[clap(long, conflicts_with = "amount")]
+        ui_amount: Option<Decimal>,
     },
 }
 
@@ -494,10 +711,40 @@ pub enum Subcommand {
 pub struct Opt {
     #[clap(flatten)]
     rpc_url: UrlArg,
+    /// Use the public RPC endpoint and known pool registry of a cluster.
+    /// An explicit `--url` or `--default-registry` takes precedence.
+    #[clap(long, global = true, arg_enum)]
+    cluster: Option<Cluster>,
     #[clap(flatten)]
     keypair: KeypairArg,
     #[clap(flatten)]
     commitment: CommitmentArg,
+    /// How many times to retry sending a transaction after a transient failure.
+    /// Network errors resend the same signed transaction, so it can land at most once.
+    /// An expired blockhash re-signs the transaction, unless the previous one landed.
+    #[clap(long, global = true, default_value_t = 0)]
+    max_retries: u32,
+    /// Wait until sent transactions are confirmed at the configured commitment level,
+    /// and fail if a transaction errors.
+    #[clap(long, global = true)]
+    confirm: bool,
+    /// When to color non-JSON output. `auto` colors only when writing to a terminal,
+    /// and respects the `NO_COLOR` environment variable.
+    #[clap(long, global = true, arg_enum, default_value = "auto")]
+    color: ColorChoice,
+    /// The pool registry used by commands whose `--pool-registry` is omitted.
+    /// Defaults to the pool registry known for `--cluster`, or the mainnet pool registry.
+    #[clap(
+        long,
+        global = true,
+        env = "GFX_SSL_DEFAULT_REGISTRY",
+        parse(try_from_str=Pubkey::try_from)
+    )]
+    default_registry: Option<Pubkey>,
+    /// Cap the number of RPC requests sent per second, e.g. for rate-limited endpoints.
+    /// Unlimited by default.
+    #[clap(long, global = true)]
+    rps: Option<NonZeroU32>,
     #[clap(subcommand)]
     subcommand: Subcommand,
 }
@@ -506,24 +753,39 @@ impl Opt {
     pub fn process(self) -> anyhow::Result<()> {
         let app = Opt::into_app();
         let matches = app.get_matches();
-        let rpc_url = self.rpc_url.resolve()?;
+        let rpc_url = match self.cluster {
+            Some(cluster) if !matches.is_present("url") => cluster.rpc_url().to_string(),
+            _ => self.rpc_url.resolve()?,
+        };
         let commitment = self.commitment.resolve()?;
-        let client = RpcClient::new_with_commitment(rpc_url, commitment);
+        let client = match self.rps {
+            Some(rps) => RpcClient::new_sender(
+                RateLimitedSender::new(rpc_url, rps),
+                RpcClientConfig::with_commitment(commitment),
+            ),
+            None => RpcClient::new_with_commitment(rpc_url, commitment),
+        };
         let signer = self.keypair.resolve(&matches)?;
         let signer_pubkey = signer.pubkey();
+        let send_config = SendConfig {
+            max_retries: self.max_retries,
+            confirm: self.confirm,
+        };
+        let color = self.color.enabled();
+        let default_registry = match self.cluster {
+            None => Some(self.default_registry.unwrap_or(MAINNET_POOL_REGISTRY)),
+            Some(cluster) => self.default_registry.or(cluster.pool_registry()),
+        };
         match self.subcommand {
             Subcommand::CreatePoolRegistry => {
                 let ix = create_pool_registry(signer_pubkey, signer_pubkey);
-                let tx = Transaction::new_signed_with_payer(
+                let signature = send_transaction_with_retries(
+                    &client,
                     &[ix],
-                    Some(&signer_pubkey),
+                    &signer_pubkey,
                     &vec![signer],
-                    client.get_latest_blockhash()?,
-                );
-                let signature = client.send_transaction(&tx).map_err(|e| {
-                    println!("{:#?}", &e);
-                    e
-                })?;
+                    send_config,
+                )?;
                 println!("{}", signature);
             }
             Subcommand::CreateEventEmitter { print_only, funder } => {
@@ -535,21 +797,19 @@ impl Opt {
                         solana_sdk::bs58::encode(message.serialize()).into_string()
                     );
                 } else {
-                    let tx = Transaction::new_signed_with_payer(
+                    let signature = send_transaction_with_retries(
+                        &client,
                         &[ix],
-                        Some(&signer_pubkey),
+                        &signer_pubkey,
                         &vec![signer],
-                        client.get_latest_blockhash()?,
-                    );
-                    let signature = client.send_transaction(&tx).map_err(|e| {
-                        println!("{:#?}", &e);
-                        e
-                    })?;
+                        send_config,
+                    )?;
                     println!("{}", signature);
                 }
             }
             Subcommand::ConfigPoolRegistry {
                 print_only,
+                diff,
                 pool_registry,
                 json_params_path,
             } => {
@@ -565,6 +825,9 @@ impl Opt {
                     .map_err(|e| anyhow!("Failed to deserialize Pool config params: {}", e))?;
                 let config: gfx_ssl_v2_interface::PoolRegistryConfig = config.into();
                 println!("{:#?}", config);
+                if diff {
+                    print_changes(&pool_registry_changes(&pool_registry_data, &config)?);
+                }
                 let ix = config_pool_registry(config, admin, pool_registry);
 
                 if print_only {
@@ -573,17 +836,14 @@ impl Opt {
                         "{}",
                         solana_sdk::bs58::encode(message.serialize()).into_string()
                     );
-                } else {
-                    let tx = Transaction::new_signed_with_payer(
+                } else if !diff {
+                    let signature = send_transaction_with_retries(
+                        &client,
                         &[ix],
-                        Some(&signer_pubkey),
+                        &signer_pubkey,
                         &vec![signer],
-                        client.get_latest_blockhash()?,
-                    );
-                    let signature = client.send_transaction(&tx).map_err(|e| {
-                        println!("{:#?}", &e);
-                        e
-                    })?;
+                        send_config,
+                    )?;
                     println!("{}", signature);
                 }
             }
@@ -591,6 +851,8 @@ impl Opt {
                 print_only,
                 pool_registry,
                 json_params_path,
+                min_price,
+                max_price,
             } => {
                 let json = &fs::read_to_string(json_params_path).map_err(|e| {
                     anyhow!("Failed to read the SSL creation params JSON file: {}", e)
@@ -627,6 +889,28 @@ impl Opt {
                     ssl_types::AssetType::Volatile => gfx_ssl_v2_interface::AssetType::Volatile,
                     ssl_types::AssetType::Stable => gfx_ssl_v2_interface::AssetType::Stable,
                 };
+                // Check that the oracle matches its declared type
+                let oracle_account = client
+                    .get_account(&oracle)
+                    .map_err(|e| anyhow!("Failed to fetch the specified oracle: {}", e))?;
+                verify_oracle_account(
+                    &oracle,
+                    oracle_type,
+                    &oracle_account.owner,
+                    &oracle_account.data,
+                )?;
+                let price = oracle_price(&oracle, oracle_type, &oracle_account.data)?;
+                println!("Current oracle price: {}", price);
+                if let Err(e) = check_price_bounds(price, min_price, max_price) {
+                    if min_price.is_some() || max_price.is_some() {
+                        return Err(anyhow!("Oracle price out of bounds: {}", e));
+                    }
+                    println!("WARNING: implausible oracle price: {}", e);
+                }
+                let math_params: gfx_ssl_v2_interface::SSLMathParams = math_params.into();
+                math_params
+                    .validate()
+                    .map_err(|e| anyhow!("Invalid math params: {}", e))?;
                 let pool_registry_data = get_pool_registry_blocking(&pool_registry, &client)
                     .map_err(|e| {
                         anyhow!("Failed to get pool registry at {}: {}", pool_registry, e)
@@ -635,7 +919,7 @@ impl Opt {
                     initial_deposit,
                     oracle_type,
                     asset_type,
-                    math_params.into(),
+                    math_params,
                     pool_registry_data.admin,
                     pool_registry,
                     mint,
@@ -650,21 +934,19 @@ impl Opt {
                         solana_sdk::bs58::encode(message.serialize()).into_string()
                     );
                 } else {
-                    let tx = Transaction::new_signed_with_payer(
+                    let signature = send_transaction_with_retries(
+                        &client,
                         &[ix],
-                        Some(&signer_pubkey),
+                        &signer_pubkey,
                         &vec![signer],
-                        client.get_latest_blockhash()?,
-                    );
-                    let signature = client.send_transaction(&tx).map_err(|e| {
-                        println!("{:#?}", &e);
-                        e
-                    })?;
+                        send_config,
+                    )?;
                     println!("{}", signature);
                 }
             }
             Subcommand::ConfigSsl {
                 print_only,
+                diff,
                 pool_registry,
                 mint,
                 suspend,
@@ -693,6 +975,32 @@ impl Opt {
                     .map_err(|e| {
                         anyhow!("Failed to get pool registry at {}: {}", pool_registry, e)
                     })?;
+                let pool = pool_registry_data.find_pool(mint).map_err(|_| {
+                    anyhow!(
+                        "Could not find mint {} in pool registry {}",
+                        mint,
+                        pool_registry
+                    )
+                })?;
+                if config.is_empty() && pool.is_suspended() == suspend {
+                    return Err(anyhow!(
+                        "Nothing to configure: no math parameters were given \
+                        and the pool's suspended status is unchanged"
+                    ));
+                }
+                if diff {
+                    let mut changes = pool.math_params.diff(&config);
+                    if pool.is_suspended() != suspend {
+                        let status = if suspend {
+                            SSLPoolStatus::Suspended
+                        } else {
+                            SSLPoolStatus::Active
+                        };
+                        changes
+                            .insert(0, ("status", pool.status().to_string(), status.to_string()));
+                    }
+                    print_changes(&changes);
+                }
                 let ix = config_ssl(
                     suspend,
                     config,
@@ -706,17 +1014,14 @@ impl Opt {
                         "{}",
                         solana_sdk::bs58::encode(message.serialize()).into_string()
                     );
-                } else {
-                    let tx = Transaction::new_signed_with_payer(
+                } else if !diff {
+                    let signature = send_transaction_with_retries(
+                        &client,
                         &[ix],
-                        Some(&signer_pubkey),
+                        &signer_pubkey,
                         &vec![signer],
-                        client.get_latest_blockhash()?,
-                    );
-                    let signature = client.send_transaction(&tx).map_err(|e| {
-                        println!("{:#?}", &e);
-                        e
-                    })?;
+                        send_config,
+                    )?;
                     println!("{}", signature);
                 }
             }
@@ -730,16 +1035,13 @@ impl Opt {
                     })?;
                 let ix =
                     config_suspend_admin(pool_registry_data.admin, pool_registry, suspend_admin);
-                let tx = Transaction::new_signed_with_payer(
+                let signature = send_transaction_with_retries(
+                    &client,
                     &[ix],
-                    Some(&signer_pubkey),
+                    &signer_pubkey,
                     &vec![signer],
-                    client.get_latest_blockhash()?,
-                );
-                let signature = client.send_transaction(&tx).map_err(|e| {
-                    println!("{:#?}", &e);
-                    e
-                })?;
+                    send_config,
+                )?;
                 println!("{}", signature);
             }
             Subcommand::CreatePair {
@@ -773,16 +1075,13 @@ impl Opt {
                         solana_sdk::bs58::encode(message.serialize()).into_string()
                     );
                 } else {
-                    let tx = Transaction::new_signed_with_payer(
+                    let signature = send_transaction_with_retries(
+                        &client,
                         &[ix],
-                        Some(&signer_pubkey),
+                        &signer_pubkey,
                         &vec![signer],
-                        client.get_latest_blockhash()?,
-                    );
-                    let signature = client.send_transaction(&tx).map_err(|e| {
-                        println!("{:#?}", &e);
-                        e
-                    })?;
+                        send_config,
+                    )?;
                     println!("{}", signature);
                 }
             }
@@ -794,40 +1093,25 @@ impl Opt {
                 fee_destination,
                 fee_bps,
             } => {
+                if let Some(fee_bps) = fee_bps {
+                    if fee_bps > 10_000 {
+                        return Err(anyhow!("Fee BPS cannot exceed 10000, got {}", fee_bps));
+                    }
+                }
                 let pool_registry_data = get_pool_registry_blocking(&pool_registry, &client)
                     .map_err(|e| {
                         anyhow!("Failed to get pool registry at {}: {}", pool_registry, e)
                     })?;
                 let pair = get_pair_blocking(&pair, &client)
                     .map_err(|e| anyhow!("Failed to fetch the specified pair: {}", e))?;
-                // Check whether the specified mint is mint one, mint two, or not found.
-                let mut is_mint_one = false;
-                if pair.mints.0 == mint {
-                    is_mint_one = true;
-                } else if pair.mints.1 != mint {
-                    return Err(anyhow!("Mint not found in pair"));
+                let mut params = ConfigPairParams::new(pair.mints.0, pair.mints.1);
+                if let Some(fee_bps) = fee_bps {
+                    params = params.normal_fee(mint, fee_bps)?;
                 }
-
-                let (mint_one_fee_rate, mint_two_fee_rate) = if is_mint_one {
-                    (fee_bps, None)
-                } else {
-                    (None, fee_bps)
-                };
-                let (mint_one_fee_dest, mint_two_fee_dest) = if is_mint_one {
-                    (fee_destination, None)
-                } else {
-                    (None, fee_destination)
-                };
-                let ix = config_pair(
-                    pool_registry_data.admin,
-                    pool_registry,
-                    pair.mints.0,
-                    pair.mints.1,
-                    mint_one_fee_rate,
-                    mint_two_fee_rate,
-                    mint_one_fee_dest,
-                    mint_two_fee_dest,
-                );
+                if let Some(fee_destination) = fee_destination {
+                    params = params.fee_destination(mint, fee_destination)?;
+                }
+                let ix = config_pair(pool_registry_data.admin, pool_registry, params);
                 if print_only {
                     let message = Message::new(&[ix], None);
                     println!(
@@ -835,32 +1119,26 @@ impl Opt {
                         solana_sdk::bs58::encode(message.serialize()).into_string()
                     );
                 } else {
-                    let tx = Transaction::new_signed_with_payer(
+                    let signature = send_transaction_with_retries(
+                        &client,
                         &[ix],
-                        Some(&signer_pubkey),
+                        &signer_pubkey,
                         &vec![signer],
-                        client.get_latest_blockhash()?,
-                    );
-                    let signature = client.send_transaction(&tx).map_err(|e| {
-                        println!("{:#?}", &e);
-                        e
-                    })?;
+                        send_config,
+                    )?;
                     println!("{}", signature);
                 }
             }
             Subcommand::CrankAllPriceHistories { pool_registry } => {
                 let registry_addrs = get_all_oracles_and_price_histories(&pool_registry, &client)?;
                 let ix = crank_oracle_price_histories(pool_registry, &registry_addrs);
-                let tx = Transaction::new_signed_with_payer(
+                let signature = send_transaction_with_retries(
+                    &client,
                     &[ix],
-                    Some(&signer_pubkey),
+                    &signer_pubkey,
                     &vec![signer],
-                    client.get_latest_blockhash()?,
-                );
-                let signature = client.send_transaction(&tx).map_err(|e| {
-                    println!("{:#?}", &e);
-                    e
-                })?;
+                    send_config,
+                )?;
                 println!("{}", signature);
             }
             Subcommand::CrankPriceHistoriesPerMint {
@@ -879,16 +1157,13 @@ impl Opt {
                     .collect();
 
                 let ix = crank_oracle_price_histories(pool_registry_addr, &registry_addrs);
-                let tx = Transaction::new_signed_with_payer(
+                let signature = send_transaction_with_retries(
+                    &client,
                     &[ix],
-                    Some(&signer_pubkey),
+                    &signer_pubkey,
                     &vec![signer],
-                    client.get_latest_blockhash()?,
-                );
-                let signature = client.send_transaction(&tx).map_err(|e| {
-                    println!("{:#?}", &e);
-                    e
-                })?;
+                    send_config,
+                )?;
                 println!("{}", signature);
             }
             Subcommand::ConfigPriceHistory {
@@ -924,16 +1199,13 @@ impl Opt {
                         solana_sdk::bs58::encode(message.serialize()).into_string()
                     );
                 } else {
-                    let tx = Transaction::new_signed_with_payer(
+                    let signature = send_transaction_with_retries(
+                        &client,
                         &[ix],
-                        Some(&signer_pubkey),
+                        &signer_pubkey,
                         &vec![signer],
-                        client.get_latest_blockhash()?,
-                    );
-                    let signature = client.send_transaction(&tx).map_err(|e| {
-                        println!("{:#?}", &e);
-                        e
-                    })?;
+                        send_config,
+                    )?;
                     println!("{}", signature);
                 }
             }
@@ -989,50 +1261,80 @@ impl Opt {
                     token_a_oracle,
                     token_b_oracle,
                 );
-                let tx = Transaction::new_signed_with_payer(
+                let signature = send_transaction_with_retries(
+                    &client,
                     &[ix],
-                    Some(&signer_pubkey),
+                    &signer_pubkey,
                     &vec![signer],
-                    client.get_latest_blockhash()?,
-                );
-                let signature = client.send_transaction(&tx).map_err(|e| {
-                    println!("{:#?}", &e);
-                    e
-                })?;
+                    send_config,
+                )?;
                 println!("{}", signature);
             }
             Subcommand::ClaimFees {
                 pool_registry,
                 mint,
+                owner,
+                force,
             } => {
-                let ix = claim_fees(pool_registry, signer_pubkey, mint);
-                let tx = Transaction::new_signed_with_payer(
+                let owner = resolve_signing_owner(owner, signer_pubkey, "claim-fees")?;
+                let liquidity_account = LiquidityAccount::address(pool_registry, mint, owner);
+                if !account_exists(&client, &liquidity_account)? {
+                    return Err(missing_liquidity_account(liquidity_account, false));
+                }
+                let liquidity_account_data =
+                    get_liquidity_account_blocking(&liquidity_account, &client)?;
+                let pool_registry_data = get_pool_registry_blocking(&pool_registry, &client)?;
+                let pool = pool_registry_data.find_pool(mint).map_err(|_| {
+                    anyhow!(
+                        "Could not find mint {} in pool registry {}",
+                        mint,
+                        pool_registry
+                    )
+                })?;
+                match estimated_claim(&liquidity_account_data, pool) {
+                    Some(fees) => println!(
+                        "Claiming an estimated {} of mint {}",
+                        token_amount::to_ui(fees, pool.mint_decimals as u32),
+                        mint
+                    ),
+                    None if force => println!(
+                        "No fees estimated to be claimable on {}, claiming anyway",
+                        liquidity_account
+                    ),
+                    None => {
+                        println!(
+                            "No fees estimated to be claimable on {}, not sending. \
+                            Pass --force to claim anyway.",
+                            liquidity_account
+                        );
+                        return Ok(());
+                    }
+                }
+                let ix = claim_fees(pool_registry, owner, mint);
+                let signature = send_transaction_with_retries(
+                    &client,
                     &[ix],
-                    Some(&signer_pubkey),
+                    &signer_pubkey,
                     &vec![signer],
-                    client.get_latest_blockhash()?,
-                );
-                let signature = client.send_transaction(&tx).map_err(|e| {
-                    println!("{:#?}", &e);
-                    e
-                })?;
+                    send_config,
+                )?;
                 println!("{}", signature);
             }
             Subcommand::CreateLiquidityAccount {
                 pool_registry,
                 mint,
+                owner,
             } => {
-                let ix = create_liquidity_account(pool_registry, signer_pubkey, mint);
-                let tx = Transaction::new_signed_with_payer(
+                let owner =
+                    resolve_signing_owner(owner, signer_pubkey, "create-liquidity-account")?;
+                let ix = create_liquidity_account(pool_registry, owner, mint);
+                let signature = send_transaction_with_retries(
+                    &client,
                     &[ix],
-                    Some(&signer_pubkey),
+                    &signer_pubkey,
                     &vec![signer],
-                    client.get_latest_blockhash()?,
-                );
-                let signature = client.send_transaction(&tx).map_err(|e| {
-                    println!("{:#?}", &e);
-                    e
-                })?;
+                    send_config,
+                )?;
                 println!("{}", signature);
             }
             Subcommand::CloseLiquidityAccount {
@@ -1047,61 +1349,79 @@ impl Opt {
                     signer_pubkey,
                     rent_recipient.unwrap_or(signer_pubkey),
                 );
-                let tx = Transaction::new_signed_with_payer(
+                let signature = send_transaction_with_retries(
+                    &client,
                     &[ix],
-                    Some(&signer_pubkey),
+                    &signer_pubkey,
                     &vec![signer],
-                    client.get_latest_blockhash()?,
-                );
-                let signature = client.send_transaction(&tx).map_err(|e| {
-                    println!("{:#?}", &e);
-                    e
-                })?;
+                    send_config,
+                )?;
                 println!("{}", signature);
             }
             Subcommand::Deposit {
                 pool_registry,
                 mint,
                 amount,
+                ui_amount,
+                create_if_missing,
+                owner,
             } => {
-                let ix = deposit(pool_registry, signer_pubkey, mint, amount);
-                let tx = Transaction::new_signed_with_payer(
-                    &[ix],
-                    Some(&signer_pubkey),
+                let owner = resolve_signing_owner(owner, signer_pubkey, "deposit")?;
+                let amount = resolve_native_amount(&client, &mint, amount, ui_amount)?;
+                let mut ixs = vec![];
+                let liquidity_account = LiquidityAccount::address(pool_registry, mint, owner);
+                if !account_exists(&client, &liquidity_account)? {
+                    if !create_if_missing {
+                        return Err(missing_liquidity_account(liquidity_account, true));
+                    }
+                    println!("Creating liquidity account {}", liquidity_account);
+                    ixs.push(create_liquidity_account(pool_registry, owner, mint));
+                }
+                ixs.push(deposit(pool_registry, owner, mint, amount));
+                let signature = send_transaction_with_retries(
+                    &client,
+                    &ixs,
+                    &signer_pubkey,
                     &vec![signer],
-                    client.get_latest_blockhash()?,
-                );
-                let signature = client.send_transaction(&tx).map_err(|e| {
-                    println!("{:#?}", &e);
-                    e
-                })?;
+                    send_config,
+                )?;
                 println!("{}", signature);
             }
             Subcommand::Withdraw {
                 pool_registry,
                 mint,
                 amount,
+                ui_amount,
+                owner,
             } => {
-                let ix = withdraw(pool_registry, signer_pubkey, mint, amount);
-                let tx = Transaction::new_signed_with_payer(
+                let owner = resolve_signing_owner(owner, signer_pubkey, "withdraw")?;
+                let liquidity_account = LiquidityAccount::address(pool_registry, mint, owner);
+                if !account_exists(&client, &liquidity_account)? {
+                    return Err(missing_liquidity_account(liquidity_account, false));
+                }
+                let amount = resolve_native_amount(&client, &mint, amount, ui_amount)?;
+                let ix = withdraw(pool_registry, owner, mint, amount);
+                let signature = send_transaction_with_retries(
+                    &client,
                     &[ix],
-                    Some(&signer_pubkey),
+                    &signer_pubkey,
                     &vec![signer],
-                    client.get_latest_blockhash()?,
-                );
-                let signature = client.send_transaction(&tx).map_err(|e| {
-                    println!("{:#?}", &e);
-                    e
-                })?;
+                    send_config,
+                )?;
                 println!("{}", signature);
             }
             Subcommand::Swap {
                 amount_in,
+                ui_amount,
+                use_lut,
                 min_out,
+                max_price_impact_bps,
                 mint_in,
                 mint_out,
                 pool_registry,
             } => {
+                let pool_registry = resolve_pool_registry(pool_registry, default_registry)?;
+                let amount_in = resolve_native_amount(&client, &mint_in, amount_in, ui_amount)?;
                 let pool_registry_data = get_pool_registry_blocking(&pool_registry, &client)
                     .map_err(|_| {
                         anyhow!("Could not find pool registry at address: {}", pool_registry)
@@ -1148,14 +1468,7 @@ impl Opt {
                 let (_, fee_destination, _) = pair
                     .find_fee_attrs(mint_in, mint_out)
                     .map_err(|_| anyhow!("Could not resolve fee destination from pair"))?;
-                let compute_budget_ix = Instruction::new_with_borsh(
-                    pubkey!("ComputeBudget111111111111111111111111111111"),
-                    &ComputeBudgetInstruction::RequestUnitsDeprecated {
-                        units: 1_000_000,
-                        additional_fee: 0,
-                    },
-                    vec![],
-                );
+                let compute_budget_ix = request_compute_units_ix();
                 let ix = swap(
                     amount_in,
                     min_out,
@@ -1167,17 +1480,123 @@ impl Opt {
                     price_hist_out.oracle_address,
                     fee_destination,
                 );
-                let tx = Transaction::new_signed_with_payer(
-                    &[compute_budget_ix, ix],
-                    Some(&signer_pubkey),
-                    &vec![signer],
-                    client.get_latest_blockhash()?,
+                if min_out > 0 || max_price_impact_bps.is_some() {
+                    let quote_ix = quote(
+                        amount_in,
+                        pool_registry,
+                        mint_in,
+                        mint_out,
+                        price_hist_in.oracle_address,
+                        price_hist_out.oracle_address,
+                    );
+                    let (amount_out, fee) = simulate_quote(
+                        &client,
+                        &[compute_budget_ix.clone(), quote_ix],
+                        &signer_pubkey,
+                    )?;
+                    if amount_out < min_out {
+                        return Err(anyhow!(
+                            "quote {} below min_out {}, not sending",
+                            amount_out,
+                            min_out
+                        ));
+                    }
+                    if let Some(max_price_impact_bps) = max_price_impact_bps {
+                        let price_in = get_oracle_price(
+                            &client,
+                            price_hist_in.oracle_address,
+                            price_hist_in.oracle_type.into(),
+                        )?;
+                        let price_out = get_oracle_price(
+                            &client,
+                            price_hist_out.oracle_address,
+                            price_hist_out.oracle_type.into(),
+                        )?;
+                        // The fee is not part of the price impact
+                        let price_impact = price_impact_bps(
+                            amount_in,
+                            ssl_in.mint_decimals,
+                            price_in,
+                            amount_out.saturating_add(fee),
+                            ssl_out.mint_decimals,
+                            price_out,
+                        )
+                        .ok_or_else(|| anyhow!("Could not compute the price impact"))?;
+                        if price_impact > Decimal::from(max_price_impact_bps) {
+                            return Err(anyhow!(
+                                "price impact {} bps exceeds {} bps, not sending",
+                                price_impact.round_dp(2),
+                                max_price_impact_bps
+                            ));
+                        }
+                    }
+                }
+                let signature = if let Some(lookup_table) = use_lut {
+                    let lookup_table = get_address_lookup_table_blocking(&lookup_table, &client)
+                        .map_err(|e| anyhow!("Failed to fetch the address lookup table: {}", e))?;
+                    send_v0_transaction_with_retries(
+                        &client,
+                        &[compute_budget_ix, ix],
+                        &signer_pubkey,
+                        &vec![signer],
+                        &[lookup_table],
+                        send_config,
+                    )?
+                } else {
+                    send_transaction_with_retries(
+                        &client,
+                        &[compute_budget_ix, ix],
+                        &signer_pubkey,
+                        &vec![signer],
+                        send_config,
+                    )?
+                };
+                println!("{}", signature);
+            }
+            Subcommand::CreatePairLookupTable {
+                pool_registry,
+                mint_one,
+                mint_two,
+            } => {
+                let pool_registry = resolve_pool_registry(pool_registry, default_registry)?;
+                let pool_registry_data = get_pool_registry_blocking(&pool_registry, &client)
+                    .map_err(|_| {
+                        anyhow!("Could not find pool registry at address: {}", pool_registry)
+                    })?;
+                let pair =
+                    get_pair_blocking(&Pair::address(pool_registry, mint_one, mint_two), &client)
+                        .map_err(|_| {
+                        anyhow!(
+                            "Could not find pair for mints: {} and {} in pool registry {}",
+                            mint_one,
+                            mint_two,
+                            pool_registry,
+                        )
+                    })?;
+                let oracle_one =
+                    get_oracle_and_price_history(&pool_registry_data, pair.mints.0, &client)?
+                        .oracle;
+                let oracle_two =
+                    get_oracle_and_price_history(&pool_registry_data, pair.mints.1, &client)?
+                        .oracle;
+                let addresses =
+                    pair_lookup_table_addresses(pool_registry, &pair, oracle_one, oracle_two);
+                let recent_slot = client.get_slot()?;
+                let (ixs, lookup_table) = create_lookup_table_with_addresses(
+                    signer_pubkey,
+                    signer_pubkey,
+                    recent_slot,
+                    addresses,
                 );
-                let signature = client.send_transaction(&tx).map_err(|e| {
-                    println!("{:#?}", &e);
-                    e
-                })?;
+                let signature = send_transaction_with_retries(
+                    &client,
+                    &ixs,
+                    &signer_pubkey,
+                    &vec![signer],
+                    send_config,
+                )?;
                 println!("{}", signature);
+                println!("Address lookup table: {}", lookup_table);
             }
             Subcommand::GetPoolRegistryAddress { seed } => {
                 let pool_registry = PoolRegistry::address(seed.unwrap_or(signer_pubkey));
@@ -1259,12 +1678,35 @@ impl Opt {
                 let liquidity_account = LiquidityAccount::address(pool_registry, mint, owner);
                 println!("{}", liquidity_account);
             }
+            Subcommand::ListRegistries { json } => {
+                let pool_registries = find_all_pool_registries_blocking(&client)?;
+                if json {
+                    let pool_registries = pool_registries
+                        .iter()
+                        .map(|(address, pool_registry)| {
+                            serde_json::json!({
+                                "address": address.to_string(),
+                                "admin": pool_registry.admin.to_string(),
+                                "num_entries": pool_registry.num_entries,
+                            })
+                        })
+                        .collect::<Vec<_>>();
+                    println!("{}", serde_json::to_string_pretty(&pool_registries)?);
+                } else {
+                    for (address, pool_registry) in pool_registries {
+                        println!(
+                            "{} (admin: {}, pools: {})",
+                            address, pool_registry.admin, pool_registry.num_entries
+                        );
+                    }
+                }
+            }
             Subcommand::GetPoolRegistry { address, raw, json } => {
                 let pool_registry = get_pool_registry_blocking(&address, &client)?;
                 pool_registry
                     .entries
                     .into_iter()
-                    .filter(|pool| *pool != SSLPool::default())
+                    .filter(|pool| !pool.is_empty())
                     .for_each(|pool| {
                         let pool_accounts_and_data =
                             SSLPoolData::from_rpc_client(pool, address, pool_registry, &client);
@@ -1272,6 +1714,7 @@ impl Opt {
                             &[pool_accounts_and_data],
                             raw,
                             json,
+                            color,
                         )
                         .unwrap();
                     })
@@ -1296,9 +1739,36 @@ impl Opt {
                     &[pool_accounts_and_data],
                     raw,
                     json,
+                    color,
                 )
                 .unwrap();
             }
+            Subcommand::GetConfig {
+                pool_registry,
+                mint,
+                out,
+            } => {
+                let pool_registry = resolve_pool_registry(pool_registry, default_registry)?;
+                let pool_registry_data = get_pool_registry_blocking(&pool_registry, &client)?;
+                let pool = pool_registry_data.find_pool(mint).map_err(|_| {
+                    anyhow!(
+                        "Failed to find pool for mint {} on pool registry {}",
+                        mint,
+                        pool_registry,
+                    )
+                })?;
+                let price_history =
+                    get_oracle_price_history_blocking(&pool.oracle_price_histories[0], &client)
+                        .map_err(|e| anyhow!("Failed to fetch the oracle price history: {}", e))?;
+                let params = ssl_types::CreateSSLParams::from_onchain(pool, &price_history)?;
+                let json = serde_json::to_string_pretty(&params)?;
+                if let Some(out) = out {
+                    fs::write(&out, json)
+                        .map_err(|e| anyhow!("Failed to write {}: {}", out.display(), e))?;
+                } else {
+                    println!("{}", json);
+                }
+            }
             Subcommand::GetPair {
                 pool_registry,
                 mint_one,
@@ -1307,6 +1777,7 @@ impl Opt {
                 json,
             } => {
                 let pool_registry_data = get_pool_registry_blocking(&pool_registry, &client)?;
+                cache_registry_mint_decimals(&pool_registry_data);
                 let pair_address = Pair::address(pool_registry, mint_one, mint_two);
                 let pair = get_pair_blocking(&pair_address, &client)?;
                 let pair_account_and_vaults = PairAccountAndVaults::from_rpc_client(
@@ -1315,61 +1786,89 @@ impl Opt {
                     pool_registry_data,
                     &client,
                 )?;
-                cli_display::<_, PairRawData, PairUiData>(&[pair_account_and_vaults], raw, json)?;
+                cli_display::<_, PairRawData, PairUiData>(
+                    &[pair_account_and_vaults],
+                    raw,
+                    json,
+                    color,
+                )?;
             }
             Subcommand::GetPairs {
                 pool_registry,
                 raw,
                 json,
+                sort_by,
+                summary,
+                no_summary,
             } => {
                 let pool_registry_data = get_pool_registry_blocking(&pool_registry, &client)?;
+                cache_registry_mint_decimals(&pool_registry_data);
                 let mints = (0..pool_registry_data.num_entries)
                     .map(|index| {
                         let pool = &pool_registry_data.entries[index as usize];
                         pool.mint
                     })
                     .collect::<Vec<Pubkey>>();
-                let mut printed: Vec<Pubkey> = vec![];
-                mints.iter().for_each(|mint_a| {
-                    mints.iter().for_each(|mint_b| {
+                let mut fetched: Vec<Pubkey> = vec![];
+                let mut pairs = vec![];
+                for mint_a in &mints {
+                    for mint_b in &mints {
                         if *mint_a != *mint_b {
                             let pair_address = Pair::address(pool_registry, *mint_a, *mint_b);
-                            if !printed.contains(&pair_address) {
-                                let pair = get_pair_blocking(&pair_address, &client).unwrap();
-                                let pair_account_and_vaults =
-                                    PairAccountAndVaults::from_rpc_client(
-                                        pair_address,
-                                        pair,
-                                        pool_registry_data,
-                                        &client,
-                                    )
-                                    .unwrap();
-                                cli_display::<_, PairRawData, PairUiData>(
-                                    &[pair_account_and_vaults],
-                                    raw,
-                                    json,
-                                )
-                                .unwrap();
+                            if !fetched.contains(&pair_address) {
+                                let pair = get_pair_blocking(&pair_address, &client)?;
+                                pairs.push(PairAccountAndVaults::from_rpc_client(
+                                    pair_address,
+                                    pair,
+                                    pool_registry_data,
+                                    &client,
+                                )?);
                             }
-                            printed.push(pair_address);
+                            fetched.push(pair_address);
                         }
-                    })
-                })
+                    }
+                }
+                let summary = show_summary(summary, no_summary, json);
+                let prices = if summary || sort_by.map_or(false, SortBy::uses_prices) {
+                    fetch_latest_prices(&pool_registry_data, &client)
+                } else {
+                    HashMap::new()
+                };
+                if let Some(sort_by) = sort_by {
+                    sort_pairs(&mut pairs, sort_by, &prices);
+                }
+                if summary {
+                    cli_display_with_summary::<_, PairRawData, PairUiData, _>(
+                        &pairs,
+                        &PairsSummary::new(&pairs, &prices),
+                        raw,
+                        json,
+                        color,
+                    )?;
+                } else {
+                    cli_display::<_, PairRawData, PairUiData>(&pairs, raw, json, color)?;
+                }
             }
             Subcommand::GetOraclePriceHistory { address, raw, json } => {
                 let price_history = get_oracle_price_history_blocking(&address, &client)?;
                 let slot = client.get_slot()?;
                 let latest_price = price_history.latest_price()?;
+                let slot_difference = slot.saturating_sub(latest_price.slot);
                 println!(
-                    "We are at slot {}, latest price is at slot {}, difference of {}",
+                    "We are at slot {}, latest price is at slot {}, difference of {} (~{:.1}s)",
                     slot,
                     latest_price.slot,
-                    slot - latest_price.slot,
+                    slot_difference,
+                    estimate_seconds_from_slots(
+                        slot_difference,
+                        &sample_block_times(&client, slot)
+                    ),
                 );
                 cli_display::<_, OraclePriceHistoryRawData, OraclePriceHistoryUiData>(
                     &[(address, price_history)],
                     raw,
                     json,
+                    color,
                 )?;
             }
             Subcommand::GetLiquidityAccount {
@@ -1378,25 +1877,42 @@ impl Opt {
                 owner,
                 raw,
                 json,
+                history,
             } => {
-                let liquidity_account_addr =
-                    LiquidityAccount::address(pool_registry, mint, owner.unwrap_or(signer_pubkey));
+                let owner = owner.unwrap_or(signer_pubkey);
+                let liquidity_account_addr = LiquidityAccount::address(pool_registry, mint, owner);
                 let liquidity_account =
                     get_liquidity_account_blocking(&liquidity_account_addr, &client)?;
                 cli_display::<_, LiquidityAccountRawData, LiquidityAccountUiData>(
                     &[(liquidity_account_addr, liquidity_account)],
                     raw,
                     json,
+                    color,
                 )?;
+                if history {
+                    let decimals = fetch_mint_decimals(&client, &mint)?;
+                    let history =
+                        fetch_liquidity_history(&client, liquidity_account_addr, owner, mint)?;
+                    for event in history.events {
+                        println!("{}", event.describe(decimals));
+                    }
+                    for (signature, e) in history.unfetched {
+                        eprintln!("Could not fetch transaction {}: {}", signature, e);
+                    }
+                }
             }
             Subcommand::GetLiquidityAccounts {
                 pool_registry,
                 owner,
                 raw,
                 json,
+                sort_by,
+                summary,
+                no_summary,
             } => {
                 let pool_registry_data = get_pool_registry_blocking(&pool_registry, &client)?;
-                let accounts = (0..pool_registry_data.num_entries)
+                cache_registry_mint_decimals(&pool_registry_data);
+                let mut accounts = (0..pool_registry_data.num_entries)
                     .flat_map(|i| {
                         let pool = &pool_registry_data.entries[i as usize];
                         let liquidity_account_addr = LiquidityAccount::address(
@@ -1408,29 +1924,281 @@ impl Opt {
                             .map(|act| (liquidity_account_addr, act))
                     })
                     .collect::<Vec<_>>();
-                cli_display::<_, LiquidityAccountRawData, LiquidityAccountUiData>(
-                    &accounts, raw, json,
-                )?;
+                let summary = show_summary(summary, no_summary, json);
+                let prices = if summary || sort_by.map_or(false, SortBy::uses_prices) {
+                    fetch_latest_prices(&pool_registry_data, &client)
+                } else {
+                    HashMap::new()
+                };
+                if let Some(sort_by) = sort_by {
+                    sort_liquidity_accounts(&mut accounts, sort_by, &prices)?;
+                }
+                if summary {
+                    cli_display_with_summary::<
+                        _,
+                        LiquidityAccountRawData,
+                        LiquidityAccountUiData,
+                        _,
+                    >(
+                        &accounts,
+                        &LiquidityAccountsSummary::new(&accounts, &prices),
+                        raw,
+                        json,
+                        color,
+                    )?;
+                } else {
+                    cli_display::<_, LiquidityAccountRawData, LiquidityAccountUiData>(
+                        &accounts, raw, json, color,
+                    )?;
+                }
+            }
+            Subcommand::DecodeAccount { raw, json, address } => {
+                let account = client.get_account(&address)?;
+                if account.owner != gfx_ssl_v2_interface::ID {
+                    return Err(anyhow!(
+                        "{} is owned by {}, not the GFX SSLv2 program",
+                        address,
+                        account.owner
+                    ));
+                }
+                let data = account.data.as_slice();
+                let kind = data
+                    .get(..8)
+                    .and_then(|disc| account_type_for_discriminator(disc.try_into().unwrap()))
+                    .ok_or_else(|| {
+                        anyhow!("{} does not match any GFX SSLv2 account type", address)
+                    })?;
+                match kind {
+                    AccountKind::PoolRegistry => {
+                        let pool_registry = PoolRegistry::try_deserialize(&mut &data[..])?;
+                        for pool in pool_registry
+                            .entries
+                            .into_iter()
+                            .filter(|pool| !pool.is_empty())
+                        {
+                            let pool_accounts_and_data =
+                                SSLPoolData::from_rpc_client(pool, address, pool_registry, &client);
+                            cli_display::<_, SSLPoolRawData, SSLPoolUiData>(
+                                &[pool_accounts_and_data],
+                                raw,
+                                json,
+                                color,
+                            )?;
+                        }
+                    }
+                    AccountKind::Pair => {
+                        let pair = Pair::try_deserialize(&mut &data[..])?;
+                        let pool_registry_data =
+                            get_pool_registry_blocking(&pair.pool_registry, &client)?;
+                        let pair_account_and_vaults = PairAccountAndVaults::from_rpc_client(
+                            address,
+                            pair,
+                            pool_registry_data,
+                            &client,
+                        )?;
+                        cli_display::<_, PairRawData, PairUiData>(
+                            &[pair_account_and_vaults],
+                            raw,
+                            json,
+                            color,
+                        )?;
+                    }
+                    AccountKind::OraclePriceHistory => {
+                        let price_history = OraclePriceHistory::try_deserialize(&mut &data[..])?;
+                        cli_display::<_, OraclePriceHistoryRawData, OraclePriceHistoryUiData>(
+                            &[(address, price_history)],
+                            raw,
+                            json,
+                            color,
+                        )?;
+                    }
+                    AccountKind::LiquidityAccount => {
+                        let liquidity_account = LiquidityAccount::try_deserialize(&mut &data[..])?;
+                        cli_display::<_, LiquidityAccountRawData, LiquidityAccountUiData>(
+                            &[(address, liquidity_account)],
+                            raw,
+                            json,
+                            color,
+                        )?;
+                    }
+                    AccountKind::EventEmitter => {
+                        let event_emitter = EventEmitter::try_deserialize(&mut &data[..])?;
+                        if json {
+                            println!(
+                                "{}",
+                                serde_json::to_string_pretty(&serde_json::json!({
+                                    "event_id": event_emitter.event_id,
+                                }))?
+                            );
+                        } else {
+                            println!("Event Emitter\nEvent ID: {}", event_emitter.event_id);
+                        }
+                    }
+                }
+            }
+            Subcommand::PairVolume {
+                pool_registry,
+                mint_one,
+                mint_two,
+                interval_ms,
+            } => {
+                let pool_registry = resolve_pool_registry(pool_registry, default_registry)?;
+                let pair_address = Pair::address(pool_registry, mint_one, mint_two);
+                let fetch_pair = || {
+                    get_pair_blocking(&pair_address, &client).map_err(|_| {
+                        anyhow!(
+                            "Could not find pair for mints: {} and {} in pool registry {}",
+                            mint_one,
+                            mint_two,
+                            pool_registry,
+                        )
+                    })
+                };
+                let mut previous = fetch_pair()?;
+                let (mint_one, mint_two) = previous.mints;
+                let decimals_one = fetch_mint_decimals(&client, &mint_one)?;
+                let decimals_two = fetch_mint_decimals(&client, &mint_two)?;
+                let interval = Duration::from_millis(interval_ms);
+                loop {
+                    thread::sleep(interval);
+                    let current = fetch_pair()?;
+                    let volume = current
+                        .historical_volume()
+                        .saturating_sub(previous.historical_volume());
+                    let (previous_fees_one, previous_fees_two) = previous.total_fees_generated();
+                    let (fees_one, fees_two) = current.total_fees_generated();
+                    println!(
+                        "{} Volume: ${} Fees: {} {}, {} {}",
+                        Utc::now().to_rfc3339(),
+                        token_amount::u128_to_ui(volume, USD_VOLUME_DECIMALS),
+                        token_amount::u128_to_ui(
+                            fees_one.saturating_sub(previous_fees_one),
+                            decimals_one
+                        ),
+                        mint_one,
+                        token_amount::u128_to_ui(
+                            fees_two.saturating_sub(previous_fees_two),
+                            decimals_two
+                        ),
+                        mint_two,
+                    );
+                    previous = current;
+                }
+            }
+            Subcommand::GetSwapRoute {
+                mint_in,
+                mint_out,
+                pool_registry,
+                amount,
+                ui_amount,
+            } => {
+                let pool_registry = resolve_pool_registry(pool_registry, default_registry)?;
+                let amount_in = resolve_native_amount(&client, &mint_in, amount, ui_amount)?;
+                let pool_registry_data = get_pool_registry_blocking(&pool_registry, &client)
+                    .map_err(|_| {
+                        anyhow!("Could not find pool registry at address: {}", pool_registry)
+                    })?;
+                let pair =
+                    get_pair_blocking(&Pair::address(pool_registry, mint_in, mint_out), &client)
+                        .map_err(|_| {
+                            anyhow!(
+                                "Could not find pair for mints: {} and {} in pool registry {}",
+                                mint_in,
+                                mint_out,
+                                pool_registry,
+                            )
+                        })?;
+                // Fees are collected in the output mint
+                let fee_rate = pair
+                    .fee_rate_for(mint_out)
+                    .ok_or_else(|| anyhow!("Could not resolve the fee rate from pair"))?;
+                let mut legs = vec![];
+                for mint in [mint_in, mint_out] {
+                    let ssl = *pool_registry_data.find_pool(mint).map_err(|_| {
+                        anyhow!(
+                            "Could not find the mint {} in pool registry {}",
+                            mint,
+                            pool_registry,
+                        )
+                    })?;
+                    let price_history =
+                        get_oracle_price_history_blocking(&ssl.oracle_price_histories[0], &client)
+                            .map_err(|_| {
+                                anyhow!(
+                            "Could not find the oracle price history for ssl pool of mint {}",
+                            mint,
+                        )
+                            })?;
+                    let price = get_oracle_price(
+                        &client,
+                        price_history.oracle_address,
+                        price_history.oracle_type(),
+                    )?;
+                    println!("Oracle price of {}: {}", mint, price);
+                    legs.push((ssl, price_history, price));
+                }
+                let (ssl_in, price_hist_in, price_in) = &legs[0];
+                let (ssl_out, price_hist_out, price_out) = &legs[1];
+                let mid_price = price_in
+                    .checked_div(*price_out)
+                    .ok_or_else(|| anyhow!("Could not divide the oracle prices"))?;
+                println!("Oracle mid price (out per in): {}", mid_price);
+                match price_hist_out.bollinger_band(
+                    ssl_out.math_params.mean_window as usize,
+                    ssl_out.math_params.std_window as usize,
+                    price_hist_in,
+                ) {
+                    Ok(bband) => {
+                        // The ratio of the output to the input token's price
+                        println!("Bollinger band mean (in per out): {}", bband.mean);
+                        println!("Bollinger band std: {}", bband.std);
+                    }
+                    Err(e) => println!("Bollinger band unavailable: {}", e),
+                }
+                println!("Fee rate: {} bps", fee_rate);
+                let compute_budget_ix = request_compute_units_ix();
+                let quote_ix = quote(
+                    amount_in,
+                    pool_registry,
+                    mint_in,
+                    mint_out,
+                    price_hist_in.oracle_address,
+                    price_hist_out.oracle_address,
+                );
+                let (amount_out, fee) =
+                    simulate_quote(&client, &[compute_budget_ix, quote_ix], &signer_pubkey)?;
+                let decimals_out = ssl_out.mint_decimals as u32;
+                println!(
+                    "Output: {} (fee: {})",
+                    token_amount::to_ui(amount_out, decimals_out),
+                    token_amount::to_ui(fee, decimals_out),
+                );
+                if let Some(spread) = price_impact_bps(
+                    amount_in,
+                    ssl_in.mint_decimals,
+                    *price_in,
+                    amount_out.saturating_add(fee),
+                    ssl_out.mint_decimals,
+                    *price_out,
+                ) {
+                    println!("Spread from oracle mid price: {} bps", spread.round_dp(2));
+                }
             }
             Subcommand::MarketMakingPnl {
                 pool_registry,
                 raw,
                 json,
+                csv_out,
             } => {
                 let pool_registry_data = get_pool_registry_blocking(&pool_registry, &client)?;
-                let latest_prices: HashMap<Pubkey, Decimal> = (0..pool_registry_data.num_entries)
-                    .map(|i| {
-                        let e = &pool_registry_data.entries[i as usize];
-                        let oracle = e.oracle_price_histories[0];
-                        let price_history =
-                            get_oracle_price_history_blocking(&oracle, &client).unwrap();
-                        (e.mint, price_history.latest_price().unwrap().price.into())
-                    })
-                    .collect();
+                let latest_prices = latest_prices(&pool_registry_data, |address| {
+                    get_oracle_price_history_blocking(address, &client).ok()
+                });
+                let mut reports = vec![];
                 pool_registry_data
                     .entries
                     .into_iter()
-                    .filter(|pool| *pool != SSLPool::default())
+                    .filter(|pool| !pool.is_empty())
                     .for_each(|pool| {
                         let pool_accounts_and_data = SSLPoolData::from_rpc_client(
                             pool,
@@ -1438,23 +2206,556 @@ impl Opt {
                             pool_registry_data,
                             &client,
                         );
-                        let mm_pnl = MarketMakingReport::generate(
-                            token_amount::to_ui(
-                                pool.total_liquidity_deposits,
-                                pool.mint_decimals as u32,
-                            ),
+                        let mm_pnl = match MarketMakingReport::generate(
                             pool_accounts_and_data,
                             &latest_prices,
-                        );
+                        ) {
+                            Ok(mm_pnl) => mm_pnl,
+                            Err(e) => {
+                                eprintln!("{}", e);
+                                return;
+                            }
+                        };
                         cli_display::<_, MarketMakingReport, MarketMakingReport>(
-                            &[mm_pnl],
+                            &[mm_pnl.clone()],
                             raw,
                             json,
+                            color,
                         )
                         .unwrap();
-                    })
+                        reports.push(mm_pnl);
+                    });
+                if let Some(path) = csv_out {
+                    let timestamp = Utc::now().to_rfc3339();
+                    MarketMakingReport::append_csv(&reports, &timestamp, &path)
+                        .map_err(|e| anyhow!("Failed to write CSV to {}: {}", path.display(), e))?;
+                }
             }
         }
         Ok(())
     }
 }
+
+/// Preset clusters for `--cluster`.
+#[derive(clap::ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cluster {
+    Mainnet,
+    Devnet,
+    Testnet,
+    Localnet,
+}
+
+impl Cluster {
+    pub fn rpc_url(self) -> &'static str {
+        match self {
+            Cluster::Mainnet => "https://api.mainnet-beta.solana.com",
+            Cluster::Devnet => "https://api.devnet.solana.com",
+            Cluster::Testnet => "https://api.testnet.solana.com",
+            Cluster::Localnet => "http://localhost:8899",
+        }
+    }
+
+    /// The pool registry to use when none is given, if one is known for this cluster.
+    pub fn pool_registry(self) -> Option<Pubkey> {
+        match self {
+            Cluster::Mainnet => Some(MAINNET_POOL_REGISTRY),
+            Cluster::Devnet | Cluster::Testnet | Cluster::Localnet => None,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+struct SendConfig {
+    max_retries: u32,
+    confirm: bool,
+}
+
+/// Sign and send a legacy transaction, see [send_with_retries].
+fn send_transaction_with_retries<T: Signers + ?Sized>(
+    client: &RpcClient,
+    instructions: &[Instruction],
+    payer: &Pubkey,
+    signers: &T,
+    send_config: SendConfig,
+) -> anyhow::Result<Signature> {
+    send_with_retries(client, send_config, |blockhash| {
+        Ok(Transaction::new_signed_with_payer(
+            instructions,
+            Some(payer),
+            signers,
+            blockhash,
+        ))
+    })
+}
+
+/// Sign and send a v0 transaction that uses address lookup tables, see [send_with_retries].
+fn send_v0_transaction_with_retries<T: Signers + ?Sized>(
+    client: &RpcClient,
+    instructions: &[Instruction],
+    payer: &Pubkey,
+    signers: &T,
+    lookup_tables: &[AddressLookupTableAccount],
+    send_config: SendConfig,
+) -> anyhow::Result<Signature> {
+    send_with_retries(client, send_config, |blockhash| {
+        let message = v0::Message::try_compile(payer, instructions, lookup_tables, blockhash)
+            .map_err(|e| ClientError::from(ClientErrorKind::Custom(e.to_string())))?;
+        Ok(VersionedTransaction::try_new(
+            VersionedMessage::V0(message),
+            signers,
+        )?)
+    })
+}
+
+/// Build, sign and send a transaction, retrying up to `max_retries` times with exponential
+/// backoff. Network errors resend the same signed transaction, since it may already have been
+/// received. An expired blockhash re-signs the transaction with a fresh blockhash, after checking
+/// that the previous signature didn't land. Other errors, e.g. a failed instruction, are not
+/// retried. Optionally waits for confirmation.
+fn send_with_retries<T, F>(
+    client: &RpcClient,
+    send_config: SendConfig,
+    build_transaction: F,
+) -> anyhow::Result<Signature>
+where
+    T: SerializableTransaction,
+    F: Fn(Hash) -> Result<T, ClientError>,
+{
+    let mut tx = build_transaction(client.get_latest_blockhash()?)?;
+    let mut attempt = 0;
+    let signature = loop {
+        let error = match client.send_transaction(&tx) {
+            Ok(signature) => break signature,
+            Err(e) => e,
+        };
+        let retry = if attempt < send_config.max_retries {
+            retry_kind(&error)
+        } else {
+            None
+        };
+        let Some(retry) = retry else {
+            eprintln!("{:#?}", &error);
+            return Err(error.into());
+        };
+        let backoff = retry_backoff(attempt);
+        eprintln!(
+            "Attempt {} failed, retrying in {:?}: {}",
+            attempt + 1,
+            backoff,
+            error
+        );
+        thread::sleep(backoff);
+        if retry == Retry::Resign {
+            // An earlier attempt may have landed even though sending it reported an error.
+            let previous = *tx.get_signature();
+            if client
+                .get_signature_statuses(&[previous])?
+                .value
+                .pop()
+                .flatten()
+                .is_some()
+            {
+                break previous;
+            }
+            tx = build_transaction(client.get_latest_blockhash()?)?;
+        }
+        attempt += 1;
+    };
+    if send_config.confirm {
+        confirm_transaction(client, &signature)?;
+    }
+    Ok(signature)
+}
+
+/// Wait time before retry number `attempt` (starting from 0), capped at [MAX_RETRY_BACKOFF].
+fn retry_backoff(attempt: u32) -> Duration {
+    INITIAL_RETRY_BACKOFF
+        .saturating_mul(2u32.saturating_pow(attempt))
+        .min(MAX_RETRY_BACKOFF)
+}
+
+/// Poll the status of a sent transaction until it reaches the client's commitment level.
+/// Fails if the transaction errored, or wasn't confirmed before [CONFIRMATION_TIMEOUT].
+fn confirm_transaction(client: &RpcClient, signature: &Signature) -> anyhow::Result<()> {
+    let start = Instant::now();
+    loop {
+        let status = client
+            .get_signature_statuses(&[*signature])?
+            .value
+            .pop()
+            .flatten();
+        if let Some(status) = status {
+            if let Some(err) = status.err {
+                return Err(anyhow!(
+                    "Transaction {} failed in slot {}: {}",
+                    signature,
+                    status.slot,
+                    err
+                ));
+            }
+            if status.satisfies_commitment(client.commitment()) {
+                println!(
+                    "Transaction {} confirmed in slot {}",
+                    signature, status.slot
+                );
+                return Ok(());
+            }
+        }
+        if start.elapsed() > CONFIRMATION_TIMEOUT {
+            return Err(anyhow!(
+                "Timed out waiting for transaction {} to be confirmed",
+                signature
+            ));
+        }
+        thread::sleep(CONFIRMATION_POLL_INTERVAL);
+    }
+}
+
+/// How to retry sending a transaction.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Retry {
+    /// Send the same signed transaction again.
+    Resend,
+    /// Sign the transaction again with a fresh blockhash.
+    Resign,
+}
+
+/// Only expired blockhashes are retried with a new signature. Network errors are retried with
+/// the same transaction, because it may have been received despite the error and a new
+/// signature could execute it twice. Anything else is not retried.
+fn retry_kind(error: &ClientError) -> Option<Retry> {
+    match error.kind() {
+        ClientErrorKind::Io(_) | ClientErrorKind::Reqwest(_) => Some(Retry::Resend),
+        _ => match error.get_transaction_error() {
+            Some(TransactionError::BlockhashNotFound) => Some(Retry::Resign),
+            _ => None,
+        },
+    }
+}
+
+/// Raise the compute unit limit for swaps and quotes, which need more than the default.
+fn request_compute_units_ix() -> Instruction {
+    Instruction::new_with_borsh(
+        pubkey!("ComputeBudget111111111111111111111111111111"),
+        &ComputeBudgetInstruction::RequestUnitsDeprecated {
+            units: 1_000_000,
+            additional_fee: 0,
+        },
+        vec![],
+    )
+}
+
+/// Simulate a transaction ending in a [quote] instruction, returning the quoted output amount
+/// and fee. Signatures aren't verified and the blockhash is replaced, so nothing is signed.
+fn simulate_quote(
+    client: &RpcClient,
+    instructions: &[Instruction],
+    payer: &Pubkey,
+) -> anyhow::Result<(u64, u64)> {
+    let tx = Transaction::new_unsigned(Message::new(instructions, Some(payer)));
+    let result = client
+        .simulate_transaction_with_config(
+            &tx,
+            RpcSimulateTransactionConfig {
+                sig_verify: false,
+                replace_recent_blockhash: true,
+                ..Default::default()
+            },
+        )?
+        .value;
+    if let Some(err) = result.err {
+        return Err(anyhow!("Quote simulation failed: {}", err));
+    }
+    let return_data = result
+        .return_data
+        .ok_or_else(|| anyhow!("Quote simulation did not return any data"))?;
+    let data = base64::decode(&return_data.data.0)?;
+    decode_quote_return_data(&data).ok_or_else(|| anyhow!("Unexpected quote return data"))
+}
+
+/// Fetch an oracle account and read its current price.
+fn get_oracle_price(
+    client: &RpcClient,
+    oracle: Pubkey,
+    oracle_type: gfx_ssl_v2_interface::OracleType,
+) -> anyhow::Result<Decimal> {
+    let data = client
+        .get_account_data(&oracle)
+        .map_err(|e| anyhow!("Failed to fetch oracle {}: {}", oracle, e))?;
+    Ok(oracle_price(&oracle, oracle_type, &data)?)
+}
+
+/// Number of slots between the two block time samples used to estimate slot duration.
+const SLOT_TIME_SAMPLE_DISTANCE: u64 = 1_000;
+
+/// Sample `(slot, block_time)` pairs for [estimate_seconds_from_slots].
+/// Slots without a block time (e.g. skipped slots) are left out.
+fn sample_block_times(client: &RpcClient, latest_slot: u64) -> Vec<(u64, i64)> {
+    [
+        latest_slot.saturating_sub(SLOT_TIME_SAMPLE_DISTANCE),
+        latest_slot,
+    ]
+    .into_iter()
+    .filter_map(|slot| client.get_block_time(slot).ok().map(|time| (slot, time)))
+    .collect()
+}
+
+/// Apply a pool registry config to a copy of the on-chain pool registry,
+/// and list the fields that would change, as `(field, before, after)`.
+fn pool_registry_changes(
+    pool_registry: &PoolRegistry,
+    config: &gfx_ssl_v2_interface::PoolRegistryConfig,
+) -> anyhow::Result<Vec<(String, String, String)>> {
+    let mut after = *pool_registry;
+    if let Some(admin) = config.new_admin {
+        after.admin = admin;
+    }
+    if let Some(suspend_admin) = config.new_suspend_admin {
+        after.suspend_admin = suspend_admin;
+    }
+    for ratio in &config.max_pool_token_ratios {
+        after
+            .set_max_pool_token_ratio(
+                &AssetType::from(ratio.input_token),
+                &AssetType::from(ratio.output_token),
+                ratio.pool_token_ratio,
+            )
+            .map_err(|e| anyhow!("Invalid max pool token ratio {:?}: {}", ratio, e))?;
+    }
+
+    let mut changes = vec![
+        (
+            "admin".to_string(),
+            pool_registry.admin.to_string(),
+            after.admin.to_string(),
+        ),
+        (
+            "suspend_admin".to_string(),
+            pool_registry.suspend_admin.to_string(),
+            after.suspend_admin.to_string(),
+        ),
+    ];
+    for input_token in &token_ratio_category::ASSET_TYPES {
+        for output_token in &token_ratio_category::ASSET_TYPES {
+            let ratio = |pool_registry: &PoolRegistry| {
+                pool_registry
+                    .max_pool_token_ratio(input_token, output_token)
+                    .map(|ratio| ratio.to_string())
+                    .map_err(|e| anyhow!("{}", e))
+            };
+            changes.push((
+                format!("max_pool_token_ratio ({}, {})", input_token, output_token),
+                ratio(pool_registry)?,
+                ratio(&after)?,
+            ));
+        }
+    }
+    changes.retain(|(_, before, after)| before != after);
+    Ok(changes)
+}
+
+/// Print configuration changes, one `field: before -> after` line each.
+fn print_changes<F: std::fmt::Display>(changes: &[(F, String, String)]) {
+    if changes.is_empty() {
+        println!("No changes");
+    }
+    for (field, before, after) in changes {
+        println!("{}: {} -> {}", field, before, after);
+    }
+}
+
+/// Use the given pool registry, or the default one.
+/// There is no default when `--cluster` names a cluster without a known pool registry.
+fn resolve_pool_registry(
+    pool_registry: Option<Pubkey>,
+    default_registry: Option<Pubkey>,
+) -> anyhow::Result<Pubkey> {
+    pool_registry
+        .or(default_registry)
+        .ok_or_else(|| anyhow!("No default pool registry for this cluster, pass --pool-registry"))
+}
+
+/// Fetch a mint's decimals, unless they are already known or were fetched before.
+fn fetch_mint_decimals(client: &RpcClient, mint: &Pubkey) -> anyhow::Result<u32> {
+    mint_decimals_or_fetch(*mint, |mint| {
+        let data = client
+            .get_account_data(mint)
+            .map_err(|e| anyhow!("Failed to fetch the specified mint: {}", e))?;
+        let mint = Mint::try_deserialize(&mut data.as_slice())
+            .map_err(|e| anyhow!("Failed to deserialize the specified mint: {}", e))?;
+        Ok(mint.decimals as u32)
+    })
+}
+
+/// Latest USD prices of the registry's mints, see [latest_prices].
+fn fetch_latest_prices(
+    pool_registry: &PoolRegistry,
+    client: &RpcClient,
+) -> HashMap<Pubkey, Decimal> {
+    latest_prices(pool_registry, |address| {
+        get_oracle_price_history_blocking(address, client).ok()
+    })
+}
+
+/// Whether a bulk display command should print a summary.
+/// `--summary` and `--no-summary` override the default, which is to summarize unless
+/// the output is JSON.
+fn show_summary(summary: bool, no_summary: bool, json: bool) -> bool {
+    summary || (!no_summary && !json)
+}
+
+/// Cache the decimals of every mint with an SSL pool in the registry,
+/// so that they can be displayed without fetching the mints.
+fn cache_registry_mint_decimals(pool_registry: &PoolRegistry) {
+    pool_registry
+        .entries
+        .iter()
+        .filter(|pool| !pool.is_empty())
+        .for_each(|pool| cache_mint_decimals(pool.mint, pool.mint_decimals as u32));
+}
+
+/// Whether an account exists at `address`, at the client's commitment level.
+fn account_exists(client: &RpcClient, address: &Pubkey) -> anyhow::Result<bool> {
+    Ok(client
+        .get_account_with_commitment(address, client.commitment())?
+        .value
+        .is_some())
+}
+
+/// An error explaining how to create a missing liquidity account.
+fn missing_liquidity_account(liquidity_account: Pubkey, can_create: bool) -> anyhow::Error {
+    let alternative = if can_create {
+        ", or pass --create-if-missing"
+    } else {
+        ""
+    };
+    anyhow!(
+        "Liquidity account {} does not exist. Run create-liquidity-account first{}",
+        liquidity_account,
+        alternative
+    )
+}
+
+/// The liquidity account owner for an instruction that requires the owner to sign.
+/// An `--owner` override is only accepted if it is the signer.
+fn resolve_signing_owner(
+    owner: Option<Pubkey>,
+    signer: Pubkey,
+    command: &str,
+) -> anyhow::Result<Pubkey> {
+    match owner {
+        Some(owner) if owner != signer => Err(anyhow!(
+            "{} requires the liquidity account owner {} to sign, \
+            so --owner can't differ from the signer {}",
+            command,
+            owner,
+            signer
+        )),
+        _ => Ok(signer),
+    }
+}
+
+/// Resolve a native token amount from either a native amount or a UI amount.
+/// Fetches the mint's decimals when converting a UI amount.
+fn resolve_native_amount(
+    client: &RpcClient,
+    mint: &Pubkey,
+    amount: Option<u64>,
+    ui_amount: Option<Decimal>,
+) -> anyhow::Result<u64> {
+    if let Some(amount) = amount {
+        return Ok(amount);
+    }
+    let ui_amount =
+        ui_amount.ok_or_else(|| anyhow!("Must provide either a native amount or a UI amount"))?;
+    let decimals = fetch_mint_decimals(client, mint)?;
+    token_amount::from_ui(ui_amount, decimals).map_err(|_| {
+        anyhow!(
+            "UI amount {} is negative, overflows a u64, or has more than {} decimal places",
+            ui_amount,
+            decimals,
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retries_resend_unless_the_blockhash_expired() {
+        let io = ClientError::from(ClientErrorKind::Io(std::io::Error::new(
+            std::io::ErrorKind::TimedOut,
+            "timed out",
+        )));
+        assert_eq!(retry_kind(&io), Some(Retry::Resend));
+
+        let expired = ClientError::from(TransactionError::BlockhashNotFound);
+        assert_eq!(retry_kind(&expired), Some(Retry::Resign));
+
+        let failed = ClientError::from(TransactionError::AccountInUse);
+        assert_eq!(retry_kind(&failed), None);
+        let custom = ClientError::from(ClientErrorKind::Custom("bad message".to_string()));
+        assert_eq!(retry_kind(&custom), None);
+    }
+
+    #[test]
+    fn retry_backoff_is_capped() {
+        assert_eq!(retry_backoff(0), INITIAL_RETRY_BACKOFF);
+        assert_eq!(retry_backoff(1), INITIAL_RETRY_BACKOFF * 2);
+        assert_eq!(retry_backoff(10), MAX_RETRY_BACKOFF);
+        assert_eq!(retry_backoff(u32::MAX), MAX_RETRY_BACKOFF);
+    }
+
+    #[test]
+    fn pool_registry_changes_lists_only_changed_fields() {
+        let mut pool_registry = PoolRegistry::default();
+        pool_registry.admin = Pubkey::new_unique();
+        pool_registry.suspend_admin = Pubkey::new_unique();
+        let new_admin = Pubkey::new_unique();
+        let config = gfx_ssl_v2_interface::PoolRegistryConfig {
+            new_admin: Some(new_admin),
+            // Set, but to the current value
+            new_suspend_admin: Some(pool_registry.suspend_admin),
+            max_pool_token_ratios: vec![token_ratio_category::MaxPoolTokenRatio::new(
+                AssetType::Stable,
+                AssetType::BlueChip,
+                1_500,
+            )],
+        };
+        assert_eq!(
+            pool_registry_changes(&pool_registry, &config).unwrap(),
+            vec![
+                (
+                    "admin".to_string(),
+                    pool_registry.admin.to_string(),
+                    new_admin.to_string()
+                ),
+                (
+                    "max_pool_token_ratio (Stable, BlueChip)".to_string(),
+                    "0.0000".to_string(),
+                    "0.1500".to_string()
+                ),
+            ]
+        );
+
+        let empty = gfx_ssl_v2_interface::PoolRegistryConfig {
+            new_admin: None,
+            new_suspend_admin: None,
+            max_pool_token_ratios: vec![],
+        };
+        assert!(pool_registry_changes(&pool_registry, &empty)
+            .unwrap()
+            .is_empty());
+
+        let invalid = gfx_ssl_v2_interface::PoolRegistryConfig {
+            max_pool_token_ratios: vec![token_ratio_category::MaxPoolTokenRatio::new(
+                AssetType::Uninitialized,
+                AssetType::Stable,
+                1_500,
+            )],
+            ..empty
+        };
+        assert!(pool_registry_changes(&pool_registry, &invalid).is_err());
+    }
+}
@@ -0,0 +1,257 @@
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use chrono::{TimeZone, Utc};
+use gfx_ssl_v2_interface::{instruction, utils::token_amount};
+use solana_client::{
+    rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient},
+    rpc_config::RpcTransactionConfig,
+};
+use solana_sdk::{compute_budget, pubkey::Pubkey, signature::Signature};
+use solana_transaction_status::{
+    option_serializer::OptionSerializer, UiTransactionEncoding, UiTransactionStatusMeta,
+    UiTransactionTokenBalance,
+};
+
+/// How many of a liquidity account's most recent transactions are searched for its history.
+pub const HISTORY_LIMIT: usize = 100;
+
+/// A change made to a liquidity account by one of its owner's instructions.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LiquidityAction {
+    Deposit(u64),
+    Withdraw(u64),
+    /// The claimed amount is read from the change in the owner's token balance. It is unknown
+    /// if the transaction doesn't report token balances, or has other instructions that
+    /// could also have changed that balance.
+    ClaimFees(Option<u64>),
+}
+
+impl LiquidityAction {
+    /// Decode SSL program instruction data, if it is a deposit, withdrawal or fee claim.
+    pub fn from_instruction_data(data: &[u8]) -> Option<Self> {
+        if data.len() < 8 {
+            return None;
+        }
+        let (discriminator, mut args) = data.split_at(8);
+        if discriminator == instruction::Deposit::DISCRIMINATOR {
+            let ix = instruction::Deposit::deserialize(&mut args).ok()?;
+            Some(Self::Deposit(ix.amount))
+        } else if discriminator == instruction::Withdraw::DISCRIMINATOR {
+            let ix = instruction::Withdraw::deserialize(&mut args).ok()?;
+            Some(Self::Withdraw(ix.amount))
+        } else if discriminator == instruction::ClaimFees::DISCRIMINATOR {
+            Some(Self::ClaimFees(None))
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct LiquidityEvent {
+    pub signature: Signature,
+    pub block_time: Option<i64>,
+    pub action: LiquidityAction,
+}
+
+impl LiquidityEvent {
+    /// One line describing the event, with amounts converted using `decimals`.
+    pub fn describe(&self, decimals: u32) -> String {
+        let time = self
+            .block_time
+            .and_then(|time| Utc.timestamp_opt(time, 0).single())
+            .map(|time| time.to_rfc3339())
+            .unwrap_or_else(|| "unknown time".to_string());
+        let action = match self.action {
+            LiquidityAction::Deposit(amount) => {
+                format!("Deposit {}", token_amount::to_ui(amount, decimals))
+            }
+            LiquidityAction::Withdraw(amount) => {
+                format!("Withdraw {}", token_amount::to_ui(amount, decimals))
+            }
+            LiquidityAction::ClaimFees(Some(amount)) => {
+                format!("Claim fees {}", token_amount::to_ui(amount, decimals))
+            }
+            LiquidityAction::ClaimFees(None) => "Claim fees".to_string(),
+        };
+        format!("{} {} {}", time, action, self.signature)
+    }
+}
+
+/// The liquidity events found by [fetch_liquidity_history].
+#[derive(Clone, Debug, Default)]
+pub struct LiquidityHistory {
+    pub events: Vec<LiquidityEvent>,
+    /// Transactions that couldn't be fetched, with the error. They may hold more events.
+    pub unfetched: Vec<(Signature, String)>,
+}
+
+/// Deposits, withdrawals and fee claims on a liquidity account, oldest first,
+/// found among its [HISTORY_LIMIT] most recent transactions.
+///
+/// Only top-level instructions are decoded, so liquidity changes made by another program
+/// through a CPI are not listed. Failed transactions are skipped, as are instructions whose
+/// accounts are only listed in an address lookup table. Transactions that can't be fetched
+/// are reported in [LiquidityHistory::unfetched] rather than failing the whole history.
+pub fn fetch_liquidity_history(
+    client: &RpcClient,
+    liquidity_account: Pubkey,
+    owner: Pubkey,
+    mint: Pubkey,
+) -> anyhow::Result<LiquidityHistory> {
+    let statuses = client.get_signatures_for_address_with_config(
+        &liquidity_account,
+        GetConfirmedSignaturesForAddress2Config {
+            limit: Some(HISTORY_LIMIT),
+            ..Default::default()
+        },
+    )?;
+    let mut history = LiquidityHistory::default();
+    for status in statuses.into_iter().rev() {
+        if status.err.is_some() {
+            continue;
+        }
+        let signature: Signature = status.signature.parse()?;
+        let tx = match client.get_transaction_with_config(
+            &signature,
+            RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::Base64),
+                max_supported_transaction_version: Some(0),
+                ..Default::default()
+            },
+        ) {
+            Ok(tx) => tx,
+            Err(e) => {
+                history.unfetched.push((signature, e.to_string()));
+                continue;
+            }
+        };
+        let Some(decoded) = tx.transaction.transaction.decode() else {
+            continue;
+        };
+        let keys = decoded.message.static_account_keys();
+        let program_ids: Vec<_> = decoded
+            .message
+            .instructions()
+            .iter()
+            .map(|ix| keys.get(ix.program_id_index as usize).copied())
+            .collect();
+        for ix in decoded.message.instructions() {
+            if keys.get(ix.program_id_index as usize) != Some(&gfx_ssl_v2_interface::ID) {
+                continue;
+            }
+            if !ix
+                .accounts
+                .iter()
+                .any(|&idx| keys.get(idx as usize) == Some(&liquidity_account))
+            {
+                continue;
+            }
+            let Some(mut action) = LiquidityAction::from_instruction_data(&ix.data) else {
+                continue;
+            };
+            if let LiquidityAction::ClaimFees(amount) = &mut action {
+                if is_only_instruction(&program_ids) {
+                    *amount = tx
+                        .transaction
+                        .meta
+                        .as_ref()
+                        .and_then(|meta| token_balance_increase(meta, &owner, &mint));
+                }
+            }
+            history.events.push(LiquidityEvent {
+                signature,
+                block_time: tx.block_time,
+                action,
+            });
+        }
+    }
+    Ok(history)
+}
+
+/// Whether a transaction with top-level instructions for these programs has a single
+/// instruction, besides compute budget ones. Only then can a change in token balances be
+/// attributed to that instruction.
+fn is_only_instruction(program_ids: &[Option<&Pubkey>]) -> bool {
+    program_ids
+        .iter()
+        .filter(|&&program_id| program_id != Some(&compute_budget::id()))
+        .count()
+        == 1
+}
+
+/// How much the transaction increased the token balance that `owner` holds of `mint`.
+fn token_balance_increase(
+    meta: &UiTransactionStatusMeta,
+    owner: &Pubkey,
+    mint: &Pubkey,
+) -> Option<u64> {
+    let (owner, mint) = (owner.to_string(), mint.to_string());
+    let balance = |balances: &OptionSerializer<Vec<UiTransactionTokenBalance>>| {
+        let OptionSerializer::Some(balances) = balances else {
+            return None;
+        };
+        balances
+            .iter()
+            .filter(|balance| {
+                balance.mint == mint
+                    && matches!(&balance.owner, OptionSerializer::Some(o) if *o == owner)
+            })
+            .map(|balance| balance.ui_token_amount.amount.parse::<u64>().ok())
+            .sum::<Option<u64>>()
+    };
+    balance(&meta.post_token_balances)?.checked_sub(balance(&meta.pre_token_balances)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gfx_ssl_v2_sdk::instructions::{claim_fees, deposit, swap, withdraw};
+
+    #[test]
+    fn decodes_liquidity_instructions() {
+        let (pool_registry, owner, mint) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let decode = |data: &[u8]| LiquidityAction::from_instruction_data(data);
+        assert_eq!(
+            decode(&deposit(pool_registry, owner, mint, 1_000).data),
+            Some(LiquidityAction::Deposit(1_000))
+        );
+        assert_eq!(
+            decode(&withdraw(pool_registry, owner, mint, 500).data),
+            Some(LiquidityAction::Withdraw(500))
+        );
+        assert_eq!(
+            decode(&claim_fees(pool_registry, owner, mint).data),
+            Some(LiquidityAction::ClaimFees(None))
+        );
+        let swap = swap(
+            1,
+            0,
+            pool_registry,
+            owner,
+            mint,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        assert_eq!(decode(&swap.data), None);
+        assert_eq!(decode(&[]), None);
+    }
+
+    #[test]
+    fn claims_are_attributed_only_when_alone() {
+        let ssl = gfx_ssl_v2_interface::ID;
+        let compute_budget = compute_budget::id();
+        let other = Pubkey::new_unique();
+        assert!(is_only_instruction(&[Some(&ssl)]));
+        assert!(is_only_instruction(&[Some(&compute_budget), Some(&ssl)]));
+        // Two claims, or a claim next to a transfer
+        assert!(!is_only_instruction(&[Some(&ssl), Some(&ssl)]));
+        assert!(!is_only_instruction(&[Some(&ssl), Some(&other)]));
+        assert!(!is_only_instruction(&[Some(&ssl), None]));
+    }
+}
@@ -5,11 +5,11 @@ use crate::{
 use anchor_lang::AccountDeserialize;
 use anchor_spl::token::TokenAccount;
 use gfx_ssl_v2_interface::{PoolRegistry, SSLPool};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use solana_client::rpc_client::RpcClient;
 use solana_sdk::pubkey::Pubkey;
 
-#[derive(Serialize, Clone, Copy)]
+#[derive(Serialize, Deserialize, Clone, Copy)]
 pub struct MainVault {
     #[serde(with = "pubkey")]
     pub address: Pubkey,
@@ -63,7 +63,7 @@ impl From<&MainVault> for MainVaultUiData {
     }
 }
 
-#[derive(Serialize, Clone, Copy)]
+#[derive(Serialize, Deserialize, Clone, Copy)]
 pub struct SecondaryVault {
     #[serde(with = "pubkey")]
     pub main_token: Pubkey,
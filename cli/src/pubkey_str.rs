@@ -35,8 +35,9 @@ pub mod pubkey {
 
 pub mod pubkey_array {
     use gfx_ssl_v2_interface::ssl_pool::MAX_NUM_ORACLES_PER_MINT;
-    use serde::{self, ser::SerializeSeq, Deserializer, Serializer};
+    use serde::{self, ser::SerializeSeq, Deserialize, Deserializer, Serializer};
     pub use solana_sdk::pubkey::Pubkey;
+    use std::str::FromStr;
 
     pub fn serialize<S>(
         pubkeys: &[Pubkey; MAX_NUM_ORACLES_PER_MINT],
@@ -56,18 +57,27 @@ pub mod pubkey_array {
     }
 
     pub fn deserialize<'de, D>(
-        _deserializer: D,
+        deserializer: D,
     ) -> Result<[Pubkey; MAX_NUM_ORACLES_PER_MINT], D::Error>
     where
         D: Deserializer<'de>,
     {
-        todo!()
+        let strings = Vec::<String>::deserialize(deserializer)?;
+        let pubkeys = strings
+            .iter()
+            .map(|s| Pubkey::from_str(s).map_err(serde::de::Error::custom))
+            .collect::<Result<Vec<_>, _>>()?;
+        let len = pubkeys.len();
+        pubkeys
+            .try_into()
+            .map_err(|_| serde::de::Error::invalid_length(len, &"an array of pubkeys"))
     }
 }
 
 pub mod pubkey_pair {
-    use serde::{self, ser::SerializeSeq, Serializer};
+    use serde::{self, ser::SerializeSeq, Deserialize, Deserializer, Serializer};
     pub use solana_sdk::pubkey::Pubkey;
+    use std::str::FromStr;
 
     pub fn serialize<S>(pubkeys: &(Pubkey, Pubkey), serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -80,4 +90,15 @@ pub mod pubkey_pair {
         seq.serialize_element(&pubkeys.1.to_string())?;
         seq.end()
     }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<(Pubkey, Pubkey), D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let (first, second) = <(String, String)>::deserialize(deserializer)?;
+        Ok((
+            Pubkey::from_str(&first).map_err(serde::de::Error::custom)?,
+            Pubkey::from_str(&second).map_err(serde::de::Error::custom)?,
+        ))
+    }
 }
@@ -0,0 +1,109 @@
+use async_trait::async_trait;
+use solana_client::{
+    client_error::Result,
+    http_sender::HttpSender,
+    rpc_request::RpcRequest,
+    rpc_sender::{RpcSender, RpcTransportStats},
+};
+use std::{
+    num::NonZeroU32,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// A token bucket that refills at `rate` tokens per second, up to one second's worth.
+#[derive(Debug)]
+struct TokenBucket {
+    rate: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: NonZeroU32, now: Instant) -> Self {
+        let rate = rate.get() as f64;
+        Self {
+            rate,
+            tokens: rate,
+            last_refill: now,
+        }
+    }
+
+    /// Take a token, or return how long to wait until one is available.
+    fn try_take(&mut self, now: Instant) -> std::result::Result<(), Duration> {
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        self.tokens = (self.tokens + elapsed.as_secs_f64() * self.rate).min(self.rate);
+        self.last_refill = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / self.rate))
+        }
+    }
+}
+
+/// An [RpcSender] that caps the number of requests per second sent over HTTP,
+/// so that every request made by an RPC client built on it is throttled.
+pub struct RateLimitedSender {
+    inner: HttpSender,
+    bucket: Mutex<TokenBucket>,
+}
+
+impl RateLimitedSender {
+    pub fn new(url: String, requests_per_second: NonZeroU32) -> Self {
+        Self {
+            inner: HttpSender::new(url),
+            bucket: Mutex::new(TokenBucket::new(requests_per_second, Instant::now())),
+        }
+    }
+}
+
+#[async_trait]
+impl RpcSender for RateLimitedSender {
+    async fn send(
+        &self,
+        request: RpcRequest,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        loop {
+            let wait = self.bucket.lock().unwrap().try_take(Instant::now());
+            match wait {
+                Ok(()) => break,
+                Err(wait) => tokio::time::sleep(wait).await,
+            }
+        }
+        self.inner.send(request, params).await
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        self.inner.get_transport_stats()
+    }
+
+    fn url(&self) -> String {
+        self.inner.url()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bucket_allows_a_burst_then_throttles() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(NonZeroU32::new(4).unwrap(), start);
+        for _ in 0..4 {
+            assert!(bucket.try_take(start).is_ok());
+        }
+        let wait = bucket.try_take(start).unwrap_err();
+        assert_eq!(wait, Duration::from_millis(250));
+        assert!(bucket.try_take(start + wait).is_ok());
+        // Idle time doesn't build up more than one second's worth of tokens
+        let later = start + Duration::from_secs(10);
+        for _ in 0..4 {
+            assert!(bucket.try_take(later).is_ok());
+        }
+        assert!(bucket.try_take(later).is_err());
+    }
+}
@@ -1,9 +1,10 @@
 use crate::pubkey_str::pubkey;
 use anchor_lang::prelude::Pubkey;
-use gfx_ssl_v2_interface::token_ratio_category;
+use anyhow::anyhow;
+use gfx_ssl_v2_interface::{token_ratio_category, OraclePriceHistory, SSLPool};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Copy, Clone, PartialEq, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CreateSSLParams {
     #[serde(with = "pubkey")]
     pub mint: Pubkey,
@@ -19,14 +20,34 @@ pub struct CreateSSLParams {
     pub math_params: SSLMathParams,
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Deserialize)]
+impl CreateSSLParams {
+    /// Reconstruct the creation parameters of an existing SSL pool, e.g. to export
+    /// its current configuration. The initial deposit is not recoverable and is set to zero.
+    pub fn from_onchain(
+        pool: &SSLPool,
+        price_history: &OraclePriceHistory,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            mint: pool.mint,
+            asset_type: pool.asset_type().try_into()?,
+            oracle: price_history.oracle_address,
+            oracle_type: price_history.oracle_type().try_into()?,
+            initial_deposit: 0,
+            number_of_slots_throttle: Some(price_history.minimum_elapsed_slots),
+            max_slot_price_staleness: Some(price_history.max_slot_price_staleness),
+            math_params: pool.math_params.try_into()?,
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub enum OracleType {
     Pyth,
     Switchboard,
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub enum AssetType {
     BlueChip,
@@ -34,6 +55,31 @@ pub enum AssetType {
     Stable,
 }
 
+impl TryFrom<gfx_ssl_v2_interface::OracleType> for OracleType {
+    type Error = anyhow::Error;
+
+    fn try_from(value: gfx_ssl_v2_interface::OracleType) -> anyhow::Result<Self> {
+        match value {
+            gfx_ssl_v2_interface::OracleType::Pyth => Ok(OracleType::Pyth),
+            gfx_ssl_v2_interface::OracleType::Switchboardv2 => Ok(OracleType::Switchboard),
+            other => Err(anyhow!("Unsupported oracle type: {:?}", other)),
+        }
+    }
+}
+
+impl TryFrom<gfx_ssl_v2_interface::AssetType> for AssetType {
+    type Error = anyhow::Error;
+
+    fn try_from(value: gfx_ssl_v2_interface::AssetType) -> anyhow::Result<Self> {
+        match value {
+            gfx_ssl_v2_interface::AssetType::BlueChip => Ok(AssetType::BlueChip),
+            gfx_ssl_v2_interface::AssetType::Volatile => Ok(AssetType::Volatile),
+            gfx_ssl_v2_interface::AssetType::Stable => Ok(AssetType::Stable),
+            other => Err(anyhow!("Unsupported asset type: {:?}", other)),
+        }
+    }
+}
+
 impl Into<gfx_ssl_v2_interface::AssetType> for AssetType {
     fn into(self) -> gfx_ssl_v2_interface::AssetType {
         match self {
@@ -52,6 +98,9 @@ pub struct SSLMathParams {
     pub std_window: u8,
     pub fixed_price_distance: u16,
     pub minimum_price_distance: u16,
+    /// Deprecated, must be zero or left out.
+    /// Max pool token ratios are configured on the pool registry.
+    #[serde(default)]
     pub max_pool_token_ratio: u16,
     pub std_weight: u16,
     pub latest_price_weight: u16,
@@ -66,7 +115,7 @@ impl Into<gfx_ssl_v2_interface::SSLMathParams> for SSLMathParams {
             minimum_price_distance: self.minimum_price_distance,
             std_weight: self.std_weight as u32,
             latest_price_weight: self.latest_price_weight,
-            _deprecated: 0,
+            _deprecated: self.max_pool_token_ratio,
             _pad0: [0; 6],
             _space: [0; 32],
             _pad1: [0; 4],
@@ -74,6 +123,29 @@ impl Into<gfx_ssl_v2_interface::SSLMathParams> for SSLMathParams {
     }
 }
 
+impl TryFrom<gfx_ssl_v2_interface::SSLMathParams> for SSLMathParams {
+    type Error = anyhow::Error;
+
+    fn try_from(value: gfx_ssl_v2_interface::SSLMathParams) -> anyhow::Result<Self> {
+        Ok(Self {
+            mean_window: value.mean_window,
+            std_window: value.std_window,
+            fixed_price_distance: value.fixed_price_distance,
+            minimum_price_distance: value.minimum_price_distance,
+            // Left out so that exported params can create a pool again,
+            // the program ignores any value stored by older pools
+            max_pool_token_ratio: 0,
+            std_weight: value.std_weight.try_into().map_err(|_| {
+                anyhow!(
+                    "std_weight {} does not fit the JSON format",
+                    value.std_weight
+                )
+            })?,
+            latest_price_weight: value.latest_price_weight,
+        })
+    }
+}
+
 /// JSON data for pair creation.
 #[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub struct PairInitializationParams(pub PairMintParams, pub PairMintParams);
@@ -124,3 +196,67 @@ impl Into<gfx_ssl_v2_interface::PoolRegistryConfig> for PoolRegistryConfig {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gfx_ssl_v2_interface::SSLPoolStatus;
+
+    #[test]
+    fn exported_config_round_trips() {
+        let math_params = gfx_ssl_v2_interface::SSLMathParams {
+            mean_window: 10,
+            std_window: 20,
+            fixed_price_distance: 5,
+            minimum_price_distance: 3,
+            std_weight: 12_000,
+            latest_price_weight: 400,
+            ..Default::default()
+        };
+        let mint = Pubkey::new_unique();
+        let pool = SSLPool::new(
+            SSLPoolStatus::Active,
+            gfx_ssl_v2_interface::AssetType::Volatile,
+            mint,
+            9,
+            255,
+            Pubkey::new_unique(),
+            1_000,
+            math_params,
+        );
+        let mut price_history = OraclePriceHistory::default();
+        price_history.oracle_type = gfx_ssl_v2_interface::OracleType::Switchboardv2.into();
+        price_history.oracle_address = Pubkey::new_unique();
+        price_history.minimum_elapsed_slots = 2;
+        price_history.max_slot_price_staleness = 30;
+
+        let params = CreateSSLParams::from_onchain(&pool, &price_history).unwrap();
+        let json = serde_json::to_string(&params).unwrap();
+        let round_tripped: CreateSSLParams = serde_json::from_str(&json).unwrap();
+        assert_eq!(round_tripped, params);
+
+        assert_eq!(round_tripped.mint, mint);
+        assert_eq!(round_tripped.asset_type, AssetType::Volatile);
+        assert_eq!(round_tripped.oracle, price_history.oracle_address);
+        assert_eq!(round_tripped.oracle_type, OracleType::Switchboard);
+        assert_eq!(round_tripped.number_of_slots_throttle, Some(2));
+        assert_eq!(round_tripped.max_slot_price_staleness, Some(30));
+        let onchain: gfx_ssl_v2_interface::SSLMathParams = round_tripped.math_params.into();
+        assert_eq!(onchain, math_params);
+    }
+
+    #[test]
+    fn uninitialized_oracle_cannot_be_exported() {
+        let pool = SSLPool::new(
+            SSLPoolStatus::Active,
+            gfx_ssl_v2_interface::AssetType::Stable,
+            Pubkey::new_unique(),
+            6,
+            255,
+            Pubkey::new_unique(),
+            0,
+            Default::default(),
+        );
+        assert!(CreateSSLParams::from_onchain(&pool, &OraclePriceHistory::default()).is_err());
+    }
+}
@@ -0,0 +1,75 @@
+use jupiter_amm_interface::{Quote, QuoteParams};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+
+/// A serializable copy of a [Quote], stamped with the mints it was computed for
+/// and the slot it was computed at, so that a quoting service can cache it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedQuote {
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    pub in_amount: u64,
+    pub out_amount: u64,
+    pub fee_amount: u64,
+    pub fee_mint: Pubkey,
+    pub fee_pct: Decimal,
+    pub not_enough_liquidity: bool,
+    pub slot: u64,
+}
+
+impl CachedQuote {
+    /// Whether the quote was computed more than `max_age` slots before `current_slot`.
+    pub fn is_stale(&self, current_slot: u64, max_age: u64) -> bool {
+        current_slot.saturating_sub(self.slot) > max_age
+    }
+}
+
+impl From<(&QuoteParams, &Quote, u64)> for CachedQuote {
+    fn from((params, quote, slot): (&QuoteParams, &Quote, u64)) -> Self {
+        Self {
+            input_mint: params.input_mint,
+            output_mint: params.output_mint,
+            in_amount: quote.in_amount,
+            out_amount: quote.out_amount,
+            fee_amount: quote.fee_amount,
+            fee_mint: quote.fee_mint,
+            fee_pct: quote.fee_pct,
+            not_enough_liquidity: quote.not_enough_liquidity,
+            slot,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_bincode() {
+        let params = QuoteParams {
+            amount: 1_000,
+            input_mint: Pubkey::new_unique(),
+            output_mint: Pubkey::new_unique(),
+            swap_mode: Default::default(),
+        };
+        let quote = Quote {
+            in_amount: 1_000,
+            out_amount: 990,
+            fee_amount: 3,
+            fee_mint: params.output_mint,
+            fee_pct: Decimal::new(30, 4),
+            ..Default::default()
+        };
+        let cached = CachedQuote::from((&params, &quote, 42));
+        assert_eq!(cached.input_mint, params.input_mint);
+        assert_eq!(cached.out_amount, 990);
+
+        let bytes = bincode::serialize(&cached).unwrap();
+        assert_eq!(bincode::deserialize::<CachedQuote>(&bytes).unwrap(), cached);
+
+        assert!(!cached.is_stale(50, 8));
+        assert!(cached.is_stale(51, 8));
+        assert!(!cached.is_stale(0, 0));
+    }
+}
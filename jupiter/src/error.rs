@@ -21,6 +21,12 @@ pub enum GfxJupiterIntegrationError {
     #[error("Program is not upgradable")]
     NotUpgradable,
 
+    #[error("Pair account {0} does not match its pool registry and mints")]
+    InconsistentInitializationData(Pubkey),
+
+    #[error("Referral fees are not supported by the GooseFX SSL program")]
+    ReferralNotSupported,
+
     #[error("Missing quote return")]
     MissingQuoteReturn,
 }
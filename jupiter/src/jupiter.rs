@@ -4,11 +4,16 @@ use anchor_lang::{
     prelude::{Clock, UpgradeableLoaderState},
     AccountDeserialize, InstructionData,
 };
-use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::{associated_token::get_associated_token_address, token::TokenAccount};
 use anyhow::Error;
 use bytemuck::bytes_of;
 use fehler::{throw, throws};
-use gfx_ssl_v2_sdk::state::{BollingerBand, OraclePriceHistory, Pair, PoolRegistry, SSLPool};
+use gfx_ssl_v2_sdk::{
+    anchor::utils::token_amount,
+    instructions::decode_quote_return_data,
+    oracle::price_impact_bps,
+    state::{BollingerBand, OraclePriceHistory, Pair, PoolRegistry, SSLPool, SSLPoolStatus},
+};
 use jupiter_amm_interface::{
     AccountMap, Amm, KeyedAccount, Quote, QuoteParams, Swap, SwapAndAccountMetas, SwapParams,
 };
@@ -16,18 +21,54 @@ use rust_decimal::Decimal;
 use solana_bpf_simulator::SBPFInstructionExecutor;
 use solana_program_runtime::log_collector::LogCollector;
 use solana_sdk::{
-    account::{AccountSharedData, ReadableAccount},
+    account::{Account, AccountSharedData, ReadableAccount},
     account_utils::StateMut,
+    hash::{hash, Hash},
     pubkey::Pubkey,
-    sysvar::clock,
+    sysvar::{self, clock},
 };
+use tracing::{debug_span, info, warn};
 
 use crate::tuple::Tuple;
 use crate::{error::GfxJupiterIntegrationError::*, swap_account_metas::get_account_metas_for_swap};
 
 type Epoch = u64; // Assuming 10 account updates for each account per s, u64 can be used for 5B years
 
+/// Number of accounts in a GooseFX SSL swap instruction.
+const SWAP_ACCOUNTS_LEN: usize = 19;
+
+/// What a thread's quote executor was last loaded with.
+///
+/// Executors are shared by every [GfxAmm] quoting on the thread, and account epochs
+/// are only comparable within one AMM. So accounts are all reloaded when the executor
+/// last quoted another pair. The program is keyed by the hash of its bytecode instead,
+/// which is only reloaded after an upgrade, whichever AMM or thread observed it.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct LoadedState {
+    pair: Pubkey,
+    program_hash: Hash,
+    /// The latest account epoch of `pair` loaded into the executor.
+    epoch: Epoch,
+}
+
+impl LoadedState {
+    /// Switch to quoting `pair`, returning the epoch after which its accounts must be loaded.
+    fn start_quote(&mut self, pair: Pubkey) -> Epoch {
+        if self.pair != pair {
+            self.pair = pair;
+            self.epoch = 0;
+        }
+        self.epoch
+    }
+
+    fn needs_program(&self, program_hash: &Hash) -> bool {
+        &self.program_hash != program_hash
+    }
+}
+
 /// Struct that implements the `jupiter_core::amm::Amm` trait.
+///
+/// `unidirectional` is intentionally left to its default, every pair swaps in both directions.
 #[derive(Debug, Clone)]
 pub struct GfxAmm {
     pair: Pubkey,
@@ -45,13 +86,29 @@ pub struct GfxAmm {
     mean_windows: Tuple<2, usize>,     // this will get updated once pool_registry is updated
     std_windows: Tuple<2, usize>,      // this will get updated once pool_registry is updated
     bbands: Tuple<2, BollingerBand<f64>>, // this will get updated once two price history is updated
+    mint_decimals: Tuple<2, u8>,       // this will get updated once pool_registry is updated
+    latest_prices: Tuple<2, Option<Decimal>>, // this will get updated once price history is updated
+    latest_price_slots: Tuple<2, Option<u64>>, // updated along with latest_prices
+    max_price_staleness: Tuple<2, u8>, // updated along with latest_prices
+    pool_statuses: Tuple<2, SSLPoolStatus>, // this will get updated once pool_registry is updated
     program_data_address: Pubkey,
+    /// Hash of the program bytecode, updated along with the program data account.
+    program_hash: Hash,
+    /// Set by [GfxAmm::set_clock], the clock sysvar is then no longer updated.
+    fixed_clock: bool,
 
     locs: HashMap<Pubkey, Tuple<2, usize>>,
     epoch: Epoch,
     accounts: HashMap<Pubkey, Option<(AccountSharedData, Epoch)>>,
 }
 
+// `Amm::clone_amm` hands out `Box<dyn Amm + Send + Sync>`. The SBF executors are
+// thread-locals rather than fields, so they don't have to be `Send` or `Sync`.
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<GfxAmm>();
+};
+
 impl GfxAmm {
     #[throws(Error)]
     fn ready(&self) {
@@ -63,6 +120,15 @@ impl GfxAmm {
         }
     }
 
+    fn fee_pct(&self, a_to_b: bool) -> Decimal {
+        let fee_rate = if a_to_b {
+            self.fee_rates[0]
+        } else {
+            self.fee_rates[1]
+        };
+        Decimal::new(fee_rate.into(), 4)
+    }
+
     fn epoch(&mut self) -> Epoch {
         let ret = self.epoch;
         self.epoch += 1;
@@ -70,6 +136,128 @@ impl GfxAmm {
     }
 }
 
+/// A [Quote] along with the prices it is based on, see [GfxAmm::quote_detailed].
+#[derive(Debug, Clone)]
+pub struct DetailedQuote {
+    pub quote: Quote,
+    /// Latest price of the input mint recorded in its oracle price history.
+    pub oracle_price_in: Decimal,
+    /// Latest price of the output mint recorded in its oracle price history.
+    pub oracle_price_out: Decimal,
+    /// Output per input, in UI amounts and before fees.
+    /// `None` for a zero amount quote.
+    pub swap_price: Option<Decimal>,
+    /// How far the quote, before fees, falls short of the oracle prices, in basis points.
+    pub price_impact_bps: Option<Decimal>,
+    /// The clock slot the quote was simulated at, see [GfxAmm::quote_at_slot].
+    pub slot: Option<u64>,
+}
+
+impl GfxAmm {
+    /// Latest prices recorded in the price histories of the pair's mints, in mint order.
+    /// `None` until the price history accounts are updated.
+    pub fn oracle_prices(&self) -> Option<(Decimal, Decimal)> {
+        Some((self.latest_prices[0]?, self.latest_prices[1]?))
+    }
+
+    /// Whether the pair can currently be quoted: both SSL pools are active, both price histories
+    /// were updated within their staleness limit as of `current_slot`, and both main vaults
+    /// hold liquidity. Routers can call this to skip pairs whose quote simulation would fail.
+    /// `false` until the pool registry, price histories and vaults are updated.
+    pub fn is_tradable(&self, current_slot: u64) -> bool {
+        (0..2).all(|i| {
+            let fresh = self.latest_price_slots[i].map_or(false, |slot| {
+                current_slot.saturating_sub(slot) <= self.max_price_staleness[i] as u64
+            });
+            self.pool_statuses[i] == SSLPoolStatus::Active
+                && fresh
+                && self
+                    .main_vault_balance(i)
+                    .map_or(false, |balance| balance > 0)
+        })
+    }
+
+    /// Token balance of the main vault of the `i`th mint's SSL pool, if it has been updated.
+    fn main_vault_balance(&self, i: usize) -> Option<u64> {
+        let signer = SSLPool::signer_address(self.pool_registry, self.mints[i]);
+        let main_vault = get_associated_token_address(&signer, &self.mints[i]);
+        let (account, _) = self.accounts.get(&main_vault)?.as_ref()?;
+        let token_account = TokenAccount::try_deserialize(&mut account.data()).ok()?;
+        Some(token_account.amount)
+    }
+
+    /// Simulate quotes with this clock instead of the live clock sysvar, e.g. to quote at a
+    /// historical slot. The clock sysvar is left out of [Amm::get_accounts_to_update],
+    /// and ignored by [Amm::update], until [GfxAmm::use_live_clock] is called.
+    ///
+    /// The Bollinger bands passed to the program are computed from the price histories alone.
+    /// Assuming the program only reads the clock for its price staleness checks, which can't be
+    /// verified from this crate, quotes over the same account snapshots are reproducible with a
+    /// fixed clock.
+    pub fn set_clock(&mut self, clock: Clock) {
+        let data = bincode::serialize(&clock).expect("Clock is serializable");
+        let account = AccountSharedData::from(Account {
+            data,
+            owner: sysvar::ID,
+            ..Default::default()
+        });
+        let epoch = self.epoch();
+        self.accounts.insert(clock::ID, Some((account, epoch)));
+        self.fixed_clock = true;
+    }
+
+    /// Undo [GfxAmm::set_clock]. The clock sysvar needs to be updated again before quoting.
+    pub fn use_live_clock(&mut self) {
+        self.accounts.insert(clock::ID, None);
+        self.fixed_clock = false;
+    }
+
+    /// The slot of the clock that quotes are simulated with, i.e. of the last clock update.
+    /// `None` until the clock sysvar is updated.
+    pub fn quote_at_slot(&self) -> Option<u64> {
+        let (account, _) = self.accounts.get(&clock::ID)?.as_ref()?;
+        let clock: Clock = bincode::deserialize(account.data()).ok()?;
+        Some(clock.slot)
+    }
+
+    /// Like [Amm::quote], but also reports the oracle prices of both mints,
+    /// and the swap price and price impact derived from them.
+    #[throws(Error)]
+    pub fn quote_detailed(&self, quote_params: &QuoteParams) -> DetailedQuote {
+        let a_to_b = quote_params.input_mint == self.mints[0];
+        let Some(prices) = self.oracle_prices() else {
+            throw!(RequiredAccountUpdate)
+        };
+        let (oracle_price_in, oracle_price_out) =
+            if a_to_b { prices } else { (prices.1, prices.0) };
+        let (decimals_in, decimals_out) = if a_to_b {
+            (self.mint_decimals[0], self.mint_decimals[1])
+        } else {
+            (self.mint_decimals[1], self.mint_decimals[0])
+        };
+        let quote = self.quote(quote_params)?;
+        let amount_out = quote.out_amount.saturating_add(quote.fee_amount);
+        let swap_price = token_amount::to_ui(amount_out, decimals_out.into())
+            .checked_div(token_amount::to_ui(quote.in_amount, decimals_in.into()));
+        let price_impact_bps = price_impact_bps(
+            quote.in_amount,
+            decimals_in,
+            oracle_price_in,
+            amount_out,
+            decimals_out,
+            oracle_price_out,
+        );
+        DetailedQuote {
+            quote,
+            oracle_price_in,
+            oracle_price_out,
+            swap_price,
+            price_impact_bps,
+            slot: self.quote_at_slot(),
+        }
+    }
+}
+
 impl Amm for GfxAmm {
     fn from_keyed_account(pair: &KeyedAccount) -> anyhow::Result<Self>
     where
@@ -88,6 +276,9 @@ impl Amm for GfxAmm {
         let data = &pair.account.data;
         let pair: Pair = Pair::try_deserialize(&mut data.as_slice())
             .map_err(|_| DeserializeFailure(pair_pubkey, "Pair".to_string()))?;
+        if !pair.verify_address(pair_pubkey) {
+            throw!(InconsistentInitializationData(pair_pubkey));
+        }
 
         accounts.insert(pair.pool_registry, None);
         locs.insert(pair.pool_registry, (1, 1).into());
@@ -116,12 +307,13 @@ impl Amm for GfxAmm {
         locs.insert(secondary_vaults.0, (5, 3).into());
         locs.insert(secondary_vaults.1, (3, 5).into());
 
-        let (_, fee_destination_a, _) = pair
-            .find_fee_attrs(mints.0, mints.1)
-            .map_err(|_| CannotResolveFeeDestination)?;
-        let (_, fee_destination_b, _) = pair
-            .find_fee_attrs(mints.1, mints.0)
-            .map_err(|_| CannotResolveFeeDestination)?;
+        // Fees are collected in the output mint
+        let fee_destination_a = pair
+            .fee_collector_for(mints.1)
+            .ok_or(CannotResolveFeeDestination)?;
+        let fee_destination_b = pair
+            .fee_collector_for(mints.0)
+            .ok_or(CannotResolveFeeDestination)?;
 
         Ok(Self {
             log: false,
@@ -131,7 +323,14 @@ impl Amm for GfxAmm {
             mean_windows: Tuple::default(),
             std_windows: Tuple::default(),
             bbands: Tuple::default(),
+            mint_decimals: Tuple::default(),
+            latest_prices: Tuple::default(),
+            latest_price_slots: Tuple::default(),
+            max_price_staleness: Tuple::default(),
+            pool_statuses: Tuple::default(),
             program_data_address: Pubkey::default(),
+            program_hash: Hash::default(),
+            fixed_clock: false,
             fee_destination: (fee_destination_a, fee_destination_b).into(),
             mints: mints.into(),
             fee_rates: pair.fee_rates.into(),
@@ -164,17 +363,27 @@ impl Amm for GfxAmm {
 
     /// Returns pubkeys of all the accounts required
     /// for providing accurate quotes and swap instructions.
+    /// Each pubkey appears once, even if it serves several roles in the pair,
+    /// e.g. a fee destination that is also a vault.
     fn get_accounts_to_update(&self) -> Vec<Pubkey> {
-        self.accounts.keys().copied().collect()
+        self.accounts
+            .keys()
+            .filter(|&&key| !(self.fixed_clock && key == clock::ID))
+            .copied()
+            .collect()
     }
 
     /// Update the account state contained in self.
     #[throws(Error)]
     fn update(&mut self, account_map: &AccountMap) {
+        let _span = debug_span!("update", pair = %self.pair).entered();
         for (pubkey, account) in account_map {
             if !self.accounts.contains_key(pubkey) {
                 continue;
             };
+            if self.fixed_clock && pubkey == &clock::ID {
+                continue;
+            }
 
             if pubkey == &self.pool_registry {
                 let pool_registry = PoolRegistry::try_deserialize(&mut account.data.as_slice())
@@ -221,6 +430,8 @@ impl Amm for GfxAmm {
                     self.price_histories[i] = ssl.oracle_price_histories[0];
                     self.mean_windows[i] = ssl.math_params.mean_window as usize;
                     self.std_windows[i] = ssl.math_params.std_window as usize;
+                    self.mint_decimals[i] = ssl.mint_decimals;
+                    self.pool_statuses[i] = ssl.status();
                 }
             } else if let Some(i) = self.price_histories.iter().position(|k| k == pubkey) {
                 let history_i = OraclePriceHistory::try_deserialize(&mut account.data.as_slice())
@@ -228,6 +439,17 @@ impl Amm for GfxAmm {
                     DeserializeFailure(*pubkey, "OraclePriceHistory".to_string())
                 })?;
 
+                // The admin may have pointed the price history at another oracle,
+                // stop tracking the old one unless the other side still uses it.
+                let previous_oracle = self.oracles[i];
+                if previous_oracle != history_i.oracle_address
+                    && previous_oracle != Pubkey::default()
+                    && previous_oracle != self.oracles[1 - i]
+                {
+                    self.accounts.remove(&previous_oracle);
+                    self.locs.remove(&previous_oracle);
+                }
+
                 if !self.accounts.contains_key(&history_i.oracle_address) {
                     self.accounts.insert(history_i.oracle_address, None);
                     if i == 0 {
@@ -237,6 +459,10 @@ impl Amm for GfxAmm {
                     }
                 }
                 self.oracles[i] = history_i.oracle_address;
+                let latest = history_i.latest_price().ok();
+                self.latest_prices[i] = latest.map(|latest| Into::<Decimal>::into(latest.price));
+                self.latest_price_slots[i] = latest.map(|latest| latest.slot);
+                self.max_price_staleness[i] = history_i.max_slot_price_staleness;
 
                 let j = 1 - i;
 
@@ -285,6 +511,7 @@ impl Amm for GfxAmm {
             if pubkey == &self.program_data_address {
                 account.data =
                     account.data[UpgradeableLoaderState::size_of_programdata_metadata()..].to_vec();
+                self.program_hash = hash(&account.data);
             }
             *maybe_existing = Some((account.into(), epoch));
         }
@@ -293,22 +520,63 @@ impl Amm for GfxAmm {
     /// Get a GooseFX SSL swap quote
     #[throws(Error)]
     fn quote(&self, quote_params: &QuoteParams) -> Quote {
-        fn create_vm() -> (SBPFInstructionExecutor<(usize, usize)>, Epoch) {
+        type Executor = (SBPFInstructionExecutor<(usize, usize)>, LoadedState);
+
+        fn create_vm() -> Executor {
             // Can increase if 10k is not enough.
             let vm = SBPFInstructionExecutor::new(40, (10, 10240)).expect("Cannot create VM");
 
-            (vm, 0)
+            (vm, LoadedState::default())
         }
 
         thread_local! {
-            pub static EXECUTOR: Tuple<2, RefCell<(SBPFInstructionExecutor<(usize, usize)>, Epoch)>> = (
+            pub static EXECUTOR: Tuple<2, RefCell<Executor>> = (
                 RefCell::new(create_vm()), RefCell::new(create_vm())
             ).into();
         }
 
-        self.ready()?;
-
         let a_to_b = quote_params.input_mint == self.mints[0];
+        let _span = debug_span!(
+            "quote",
+            pair = %self.pair,
+            a_to_b,
+            amount_in = quote_params.amount,
+        )
+        .entered();
+
+        // Nothing to simulate
+        if quote_params.amount == 0 {
+            return Quote {
+                not_enough_liquidity: false,
+                min_in_amount: None,
+                min_out_amount: None,
+                in_amount: 0,
+                out_amount: 0,
+                fee_amount: 0,
+                fee_mint: quote_params.output_mint,
+                fee_pct: self.fee_pct(a_to_b),
+            };
+        }
+
+        // Swaps against a suspended pool revert, so don't bother simulating
+        if self
+            .pool_statuses
+            .iter()
+            .any(|s| *s == SSLPoolStatus::Suspended)
+        {
+            return Quote {
+                not_enough_liquidity: true,
+                min_in_amount: None,
+                min_out_amount: None,
+                in_amount: quote_params.amount,
+                out_amount: 0,
+                fee_amount: 0,
+                fee_mint: quote_params.output_mint,
+                fee_pct: self.fee_pct(a_to_b),
+            };
+        }
+
+        self.ready()?;
 
         // This can be removed if the BPF impl is fast enough
         let bband = if a_to_b {
@@ -331,8 +599,9 @@ impl Amm for GfxAmm {
             };
             let refmut = &mut *refmut;
 
-            let (vm, vm_epoch) = (&mut refmut.0, &mut refmut.1);
-            let mut new_epoch = *vm_epoch;
+            let (vm, loaded) = (&mut refmut.0, &mut refmut.1);
+            let vm_epoch = loaded.start_quote(self.pair);
+            let mut new_epoch = vm_epoch;
 
             if self.log {
                 *vm.context_mut().log_collector_mut() = Some(LogCollector::new_ref());
@@ -340,16 +609,23 @@ impl Amm for GfxAmm {
             vm.update_instruction(&ix)?;
             for (&key, maybe_account) in &self.accounts {
                 let &Some((ref account, account_epoch)) = maybe_account else {
+                    warn!(%key, "missing account");
                     throw!(RequiredAccountUpdate);
                 };
 
-                if account_epoch <= *vm_epoch {
+                if key == self.program_data_address {
+                    if loaded.needs_program(&self.program_hash) {
+                        vm.update_program(&gfx_ssl_v2_sdk::ID, account, true)?;
+                        loaded.program_hash = self.program_hash;
+                    }
                     continue;
                 }
 
-                if key == self.program_data_address {
-                    vm.update_program(&gfx_ssl_v2_sdk::ID, account, true)?;
-                } else if key == clock::ID {
+                if account_epoch <= vm_epoch {
+                    continue;
+                }
+
+                if key == clock::ID {
                     let clock: Clock = bincode::deserialize(&account.data())?;
                     vm.context_mut().sysvars_mut().set_clock(clock);
                 } else if key == gfx_ssl_v2_sdk::ID {
@@ -368,7 +644,7 @@ impl Amm for GfxAmm {
                 new_epoch = new_epoch.max(account_epoch);
             }
 
-            *vm_epoch = new_epoch;
+            loaded.epoch = new_epoch;
 
             let result = vm.execute();
             let data = vm.get_return_data().cloned();
@@ -379,7 +655,7 @@ impl Amm for GfxAmm {
 
         if let Some(logger) = logger {
             let logs = logger.borrow().get_recorded_content().to_vec();
-            println!("Logs {:?}", logs);
+            info!(?logs, "program logs");
         }
 
         let _ = result?;
@@ -388,19 +664,9 @@ impl Amm for GfxAmm {
             throw!(MissingQuoteReturn)
         };
 
-        if data.len() != 16 {
-            throw!(MissingQuoteReturn);
-        }
-
-        let output: u64 = u64::from_le_bytes(data[..8].try_into().unwrap());
-        let fee: u64 = u64::from_le_bytes(data[8..16].try_into().unwrap());
-
-        let fee_pct = if a_to_b {
-            self.fee_rates[0]
-        } else {
-            self.fee_rates[1]
+        let Some((output, fee)) = decode_quote_return_data(&data) else {
+            throw!(MissingQuoteReturn)
         };
-        let fee_pct = Decimal::new(fee_pct.into(), 4);
 
         let quote = Quote {
             not_enough_liquidity: false,
@@ -410,18 +676,27 @@ impl Amm for GfxAmm {
             out_amount: output,
             fee_amount: fee,
             fee_mint: quote_params.output_mint,
-            fee_pct,
+            fee_pct: self.fee_pct(a_to_b),
         };
         quote
     }
 
     /// Get account metas for a GFX swap instruction,
     /// and marker denoting a [SwapLeg::Swap], and a [Swap::GooseFX].
+    ///
+    /// The SSL program has no referral fee account, so this fails if `quote_mint_to_referrer`
+    /// holds a referrer for either mint of the pair, rather than silently dropping the referral.
     #[throws(Error)]
     fn get_swap_and_account_metas(&self, swap_params: &SwapParams) -> SwapAndAccountMetas {
         // We need these accounts to be updated in order to create swap account metas
         self.ready()?;
 
+        if let Some(referrers) = swap_params.quote_mint_to_referrer {
+            if self.mints.any(|mint| referrers.contains_key(mint)) {
+                throw!(ReferralNotSupported);
+            }
+        }
+
         let (mint_in, mint_out, input_token_price_history, output_token_price_history) =
             if swap_params.source_mint == self.mints[0] {
                 (
@@ -458,8 +733,445 @@ impl Amm for GfxAmm {
         }
     }
 
+    /// The SSL program only quotes and swaps an exact input amount.
+    fn supports_exact_out(&self) -> bool {
+        false
+    }
+
+    /// Price history and oracle accounts are discovered in [GfxAmm::update],
+    /// and change when the admin reassigns a pool's price history or oracle.
+    /// Hosts must call `get_accounts_to_update` again after every update.
+    fn has_dynamic_accounts(&self) -> bool {
+        true
+    }
+
+    /// The simulator executes the SSL program from its program-data account,
+    /// which is reported once the program account has been loaded in [GfxAmm::update].
+    fn program_dependencies(&self) -> Vec<(Pubkey, String)> {
+        let mut dependencies = vec![(gfx_ssl_v2_sdk::ID, "gfx_ssl_v2".to_string())];
+        if self.program_data_address != Pubkey::default() {
+            dependencies.push((
+                self.program_data_address,
+                "gfx_ssl_v2_program_data".to_string(),
+            ));
+        }
+        dependencies
+    }
+
+    /// Number of accounts in the swap instruction, see [get_account_metas_for_swap].
+    fn get_accounts_len(&self) -> usize {
+        SWAP_ACCOUNTS_LEN
+    }
+
     /// Clone this object in a [Box].
     fn clone_amm(&self) -> Box<dyn Amm + Send + Sync> {
         Box::new(self.clone())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::GfxJupiterIntegrationError;
+    use anchor_lang::solana_program::program_pack::Pack;
+    use anchor_lang::AccountSerialize;
+    use gfx_ssl_v2_sdk::state::HistoricalPrice;
+    use std::collections::HashSet;
+
+    fn keyed_pair_account(key: Pubkey, pair: &Pair) -> KeyedAccount {
+        let mut data = vec![];
+        pair.try_serialize(&mut data).unwrap();
+        KeyedAccount {
+            key,
+            account: Account {
+                data,
+                owner: gfx_ssl_v2_sdk::ID,
+                ..Default::default()
+            },
+            params: None,
+        }
+    }
+
+    /// A pair of two new mints under a new pool registry.
+    fn new_pair() -> Pair {
+        Pair {
+            pool_registry: Pubkey::new_unique(),
+            mints: Pair::normalize_mint_order(Pubkey::new_unique(), Pubkey::new_unique()),
+            ..Default::default()
+        }
+    }
+
+    /// An AMM for `pair`, stored at its expected address.
+    fn amm_for_pair(pair: &Pair) -> GfxAmm {
+        let key = Pair::address(pair.pool_registry, pair.mints.0, pair.mints.1);
+        GfxAmm::from_keyed_account(&keyed_pair_account(key, pair)).unwrap()
+    }
+
+    /// An AMM for [new_pair], none of whose accounts have been updated.
+    fn amm_for_new_pair() -> GfxAmm {
+        amm_for_pair(&new_pair())
+    }
+
+    fn account(data: Vec<u8>) -> Account {
+        Account {
+            data,
+            ..Default::default()
+        }
+    }
+
+    /// Update `amm` with a pool registry holding the pools of both of its mints,
+    /// using single-entry windows. Returns each pool's price history address.
+    fn update_pool_registry(amm: &mut GfxAmm, statuses: [SSLPoolStatus; 2]) -> [Pubkey; 2] {
+        let price_histories = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let mut registry = PoolRegistry::default();
+        for i in 0..2 {
+            let entry = &mut registry.entries[i];
+            entry.mint = amm.mints[i];
+            entry.status = statuses[i] as u8;
+            entry.oracle_price_histories[0] = price_histories[i];
+            entry.math_params.mean_window = 1;
+            entry.math_params.std_window = 1;
+        }
+        let mut data = vec![];
+        registry.try_serialize(&mut data).unwrap();
+        amm.update(&HashMap::from([(amm.pool_registry, account(data))]))
+            .unwrap();
+        price_histories
+    }
+
+    /// Price history of `amm`'s `i`th mint, with a staleness limit of 10 slots.
+    /// `price`, if any, was recorded at slot 100.
+    fn price_history_account(
+        amm: &GfxAmm,
+        i: usize,
+        oracle_address: Pubkey,
+        price: Option<Decimal>,
+    ) -> Account {
+        let mut history = OraclePriceHistory::default();
+        history.pool_registry = amm.pool_registry;
+        history.oracle_address = oracle_address;
+        history.mint = amm.mints[i];
+        history.max_slot_price_staleness = 10;
+        if let Some(price) = price {
+            history.push(HistoricalPrice {
+                price: price.try_into().unwrap(),
+                slot: 100,
+            });
+        }
+        let mut data = vec![];
+        history.try_serialize(&mut data).unwrap();
+        account(data)
+    }
+
+    #[test]
+    fn rejects_pair_at_wrong_address() {
+        let keyed_account = keyed_pair_account(Pubkey::new_unique(), &new_pair());
+        assert!(GfxAmm::from_keyed_account(&keyed_account).is_err());
+    }
+
+    #[test]
+    fn reports_exact_in_only() {
+        assert!(!amm_for_new_pair().supports_exact_out());
+    }
+
+    #[test]
+    fn zero_amount_quote_skips_simulation() {
+        // No accounts have been updated, so a simulated quote would fail
+        let amm = amm_for_new_pair();
+        let quote = amm
+            .quote(&QuoteParams {
+                amount: 0,
+                input_mint: amm.mints[0],
+                output_mint: amm.mints[1],
+                swap_mode: Default::default(),
+            })
+            .unwrap();
+        assert_eq!(quote.in_amount, 0);
+        assert_eq!(quote.out_amount, 0);
+        assert_eq!(quote.fee_amount, 0);
+    }
+
+    #[test]
+    fn accounts_to_update_are_unique() {
+        let pair = new_pair();
+        let ssl_signer = SSLPool::signer_address(pair.pool_registry, pair.mints.0);
+        let main_vault = get_associated_token_address(&ssl_signer, &pair.mints.0);
+        let amm = amm_for_pair(&Pair {
+            // Fee destination coincides with a vault
+            fee_collector: (main_vault, main_vault),
+            ..pair
+        });
+
+        let keys = amm.get_accounts_to_update();
+        let unique = keys.iter().collect::<HashSet<_>>();
+        assert_eq!(keys.len(), unique.len());
+        assert!(keys.contains(&main_vault));
+        assert!(keys.contains(&amm.pool_registry));
+    }
+
+    #[test]
+    fn reports_swap_accounts_len() {
+        let amm = amm_for_new_pair();
+        let account_metas = get_account_metas_for_swap(
+            amm.pool_registry,
+            Pubkey::new_unique(),
+            amm.mints[0],
+            amm.mints[1],
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        assert_eq!(amm.get_accounts_len(), account_metas.len());
+    }
+
+    #[test]
+    fn reports_program_data_dependency() {
+        let mut amm = amm_for_new_pair();
+        assert_eq!(
+            amm.program_dependencies(),
+            vec![(gfx_ssl_v2_sdk::ID, "gfx_ssl_v2".to_string())]
+        );
+
+        let programdata_address = Pubkey::new_unique();
+        let data = bincode::serialize(&UpgradeableLoaderState::Program {
+            programdata_address,
+        })
+        .unwrap();
+        amm.update(&HashMap::from([(gfx_ssl_v2_sdk::ID, account(data))]))
+            .unwrap();
+        let dependencies = amm.program_dependencies();
+        assert_eq!(dependencies.len(), 2);
+        assert_eq!(dependencies[1].0, programdata_address);
+    }
+
+    #[test]
+    fn tracks_reassigned_oracle() {
+        let mut amm = amm_for_new_pair();
+        assert!(amm.has_dynamic_accounts());
+
+        let price_histories = update_pool_registry(&mut amm, [SSLPoolStatus::Active; 2]);
+        assert!(amm.get_accounts_to_update().contains(&price_histories[0]));
+
+        let history_account = |amm: &GfxAmm, oracle_address| {
+            let history = price_history_account(amm, 0, oracle_address, None);
+            HashMap::from([(price_histories[0], history)])
+        };
+        let old_oracle = Pubkey::new_unique();
+        amm.update(&history_account(&amm, old_oracle)).unwrap();
+        assert!(amm.get_accounts_to_update().contains(&old_oracle));
+
+        let new_oracle = Pubkey::new_unique();
+        amm.update(&history_account(&amm, new_oracle)).unwrap();
+        let keys = amm.get_accounts_to_update();
+        assert!(keys.contains(&new_oracle));
+        assert!(!keys.contains(&old_oracle));
+    }
+
+    #[test]
+    fn reports_oracle_prices_after_update() {
+        let mut amm = amm_for_new_pair();
+        assert_eq!(amm.oracle_prices(), None);
+
+        let price_histories = update_pool_registry(&mut amm, [SSLPoolStatus::Active; 2]);
+        let account_map = HashMap::from([
+            (
+                price_histories[0],
+                price_history_account(&amm, 0, Pubkey::new_unique(), Some(Decimal::from(20))),
+            ),
+            (
+                price_histories[1],
+                price_history_account(&amm, 1, Pubkey::new_unique(), Some(Decimal::ONE)),
+            ),
+        ]);
+        amm.update(&account_map).unwrap();
+        assert_eq!(amm.oracle_prices(), Some((Decimal::from(20), Decimal::ONE)));
+    }
+
+    /// An AMM whose pool registry, price histories and main vaults have been updated.
+    /// Prices were last recorded at slot 100, with a staleness limit of 10 slots.
+    fn updated_amm(statuses: [SSLPoolStatus; 2], main_vault_balances: [u64; 2]) -> GfxAmm {
+        let mut amm = amm_for_new_pair();
+        let price_histories = update_pool_registry(&mut amm, statuses);
+
+        let mut account_map = HashMap::new();
+        for i in 0..2 {
+            let history = price_history_account(&amm, i, Pubkey::new_unique(), Some(Decimal::ONE));
+            account_map.insert(price_histories[i], history);
+
+            let signer = SSLPool::signer_address(amm.pool_registry, amm.mints[i]);
+            let token_account = spl_token::state::Account {
+                mint: amm.mints[i],
+                owner: signer,
+                amount: main_vault_balances[i],
+                state: spl_token::state::AccountState::Initialized,
+                ..Default::default()
+            };
+            let mut data = vec![0; spl_token::state::Account::LEN];
+            token_account.pack_into_slice(&mut data);
+            account_map.insert(
+                get_associated_token_address(&signer, &amm.mints[i]),
+                account(data),
+            );
+        }
+        amm.update(&account_map).unwrap();
+        amm
+    }
+
+    #[test]
+    fn tradable_pair() {
+        let active = [SSLPoolStatus::Active; 2];
+        let amm = updated_amm(active, [1_000, 1_000]);
+        assert!(amm.is_tradable(105));
+        assert!(amm.is_tradable(110));
+
+        // Stale oracle
+        assert!(!amm.is_tradable(111));
+        // Suspended pool
+        let amm = updated_amm(
+            [SSLPoolStatus::Active, SSLPoolStatus::Suspended],
+            [1_000, 1_000],
+        );
+        assert!(!amm.is_tradable(105));
+        // Empty pool
+        let amm = updated_amm(active, [1_000, 0]);
+        assert!(!amm.is_tradable(105));
+    }
+
+    #[test]
+    fn suspended_pool_quote_skips_simulation() {
+        // The program data was never updated, so a simulated quote would fail
+        let amm = updated_amm(
+            [SSLPoolStatus::Active, SSLPoolStatus::Suspended],
+            [1_000, 1_000],
+        );
+        for (input_mint, output_mint) in
+            [(amm.mints[0], amm.mints[1]), (amm.mints[1], amm.mints[0])]
+        {
+            let quote = amm
+                .quote(&QuoteParams {
+                    amount: 100,
+                    input_mint,
+                    output_mint,
+                    swap_mode: Default::default(),
+                })
+                .unwrap();
+            assert!(quote.not_enough_liquidity);
+            assert_eq!(quote.out_amount, 0);
+        }
+    }
+
+    #[test]
+    fn not_tradable_before_update() {
+        assert!(!amm_for_new_pair().is_tradable(0));
+    }
+
+    #[test]
+    fn reports_quote_slot() {
+        let mut amm = amm_for_new_pair();
+        assert_eq!(amm.quote_at_slot(), None);
+
+        let clock = Clock {
+            slot: 123,
+            ..Default::default()
+        };
+        let account_map =
+            HashMap::from([(clock::ID, account(bincode::serialize(&clock).unwrap()))]);
+        amm.update(&account_map).unwrap();
+        assert_eq!(amm.quote_at_slot(), Some(123));
+    }
+
+    #[test]
+    fn fixed_clock_is_not_updated() {
+        let mut amm = amm_for_new_pair();
+        assert!(amm.get_accounts_to_update().contains(&clock::ID));
+
+        amm.set_clock(Clock {
+            slot: 150_000_000,
+            ..Default::default()
+        });
+        assert_eq!(amm.quote_at_slot(), Some(150_000_000));
+        assert!(!amm.get_accounts_to_update().contains(&clock::ID));
+
+        let live_clock = Clock {
+            slot: 250_000_000,
+            ..Default::default()
+        };
+        let account_map =
+            HashMap::from([(clock::ID, account(bincode::serialize(&live_clock).unwrap()))]);
+        amm.update(&account_map).unwrap();
+        assert_eq!(amm.quote_at_slot(), Some(150_000_000));
+
+        amm.use_live_clock();
+        assert!(amm.get_accounts_to_update().contains(&clock::ID));
+        assert_eq!(amm.quote_at_slot(), None);
+        amm.update(&account_map).unwrap();
+        assert_eq!(amm.quote_at_slot(), Some(250_000_000));
+    }
+
+    #[test]
+    fn executor_reloads_upgraded_program() {
+        let mut amm = amm_for_new_pair();
+        let program_data_address = Pubkey::new_unique();
+        amm.program_data_address = program_data_address;
+        amm.accounts.insert(program_data_address, None);
+        let program_data = |bytecode: &[u8]| {
+            let mut data = vec![0; UpgradeableLoaderState::size_of_programdata_metadata()];
+            data.extend_from_slice(bytecode);
+            HashMap::from([(program_data_address, account(data))])
+        };
+
+        // An executor on another thread loaded the original program
+        amm.update(&program_data(b"v1")).unwrap();
+        let mut loaded = LoadedState::default();
+        assert!(loaded.needs_program(&amm.program_hash));
+        loaded.start_quote(amm.pair);
+        loaded.program_hash = amm.program_hash;
+        loaded.epoch = amm.epoch - 1;
+
+        // Refetching the same bytecode doesn't reload it
+        amm.update(&program_data(b"v1")).unwrap();
+        assert!(!loaded.needs_program(&amm.program_hash));
+        amm.update(&program_data(b"v2")).unwrap();
+        assert!(loaded.needs_program(&amm.program_hash));
+
+        // Quoting another pair reloads every account
+        assert_eq!(loaded.start_quote(amm.pair), loaded.epoch);
+        assert_eq!(loaded.start_quote(Pubkey::new_unique()), 0);
+    }
+
+    #[test]
+    fn rejects_referral_for_pair_mints() {
+        let mut amm = updated_amm([SSLPoolStatus::Active; 2], [1_000, 1_000]);
+        amm.program_data_address = Pubkey::new_unique();
+        let jupiter_program_id = Pubkey::new_unique();
+        let swap_params = |quote_mint_to_referrer| SwapParams {
+            in_amount: 100,
+            source_mint: amm.mints[0],
+            destination_mint: amm.mints[1],
+            source_token_account: Pubkey::new_unique(),
+            destination_token_account: Pubkey::new_unique(),
+            token_transfer_authority: Pubkey::new_unique(),
+            open_order_address: None,
+            quote_mint_to_referrer,
+            jupiter_program_id: &jupiter_program_id,
+        };
+
+        assert!(amm.get_swap_and_account_metas(&swap_params(None)).is_ok());
+        // Referrers for other mints don't concern this pair
+        let other_referrer = HashMap::from([(Pubkey::new_unique(), Pubkey::new_unique())]);
+        assert!(amm
+            .get_swap_and_account_metas(&swap_params(Some(&other_referrer)))
+            .is_ok());
+
+        for mint in amm.mints.iter() {
+            let referrer = HashMap::from([(*mint, Pubkey::new_unique())]);
+            let err = amm
+                .get_swap_and_account_metas(&swap_params(Some(&referrer)))
+                .unwrap_err();
+            assert!(matches!(
+                err.downcast_ref::<GfxJupiterIntegrationError>(),
+                Some(GfxJupiterIntegrationError::ReferralNotSupported)
+            ));
+        }
+    }
+}
@@ -1,3 +1,4 @@
+pub mod cached_quote;
 pub mod error;
 pub mod jupiter;
 pub mod swap_account_metas;
@@ -76,6 +76,11 @@ where
         indices.map(|i| self[i].clone()).into()
     }
 
+    /// A reversed copy, e.g. to turn values oriented for an a-to-b swap
+    /// into the b-to-a orientation. Iterating the result matches `self.iter().rev()`.
+    ///
+    /// This leaves `self` untouched, unlike the in-place `<[T]>::reverse`
+    /// reachable through [DerefMut], which is what a call resolves to if `T` isn't [Clone].
     pub fn reverse(&self) -> Tuple<N, T> {
         let mut ret = self.0.clone();
         ret.reverse();
@@ -107,3 +112,25 @@ impl<const N: usize, T> DerefMut for Tuple<N, T> {
         &mut self.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reverse_returns_a_reversed_copy() {
+        let tuple: Tuple<2, u8> = (1, 2).into();
+        let reversed = tuple.reverse();
+        assert_eq!(*reversed, [2, 1]);
+        assert_eq!(*tuple, [1, 2]);
+        assert_eq!(*reversed.reverse(), *tuple);
+    }
+
+    #[test]
+    fn iteration_orders() {
+        let tuple: Tuple<2, u8> = (1, 2).into();
+        assert_eq!(tuple.iter().copied().collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(tuple.iter().rev().copied().collect::<Vec<_>>(), vec![2, 1]);
+        assert!(tuple.reverse().iter().eq(tuple.iter().rev()));
+    }
+}
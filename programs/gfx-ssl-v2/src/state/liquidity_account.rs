@@ -1,4 +1,4 @@
-use crate::PDAIdentifier;
+use crate::{PDAIdentifier, SSLPool};
 use anchor_lang::prelude::*;
 #[cfg(feature = "no-entrypoint")]
 use chrono::{DateTime, NaiveDateTime, Utc};
@@ -91,6 +91,30 @@ impl LiquidityAccount {
     pub fn address(pool_registry: Pubkey, mint: Pubkey, owner: Pubkey) -> Pubkey {
         Self::get_address(&[pool_registry.as_ref(), mint.as_ref(), owner.as_ref()])
     }
+
+    /// Estimate of the native amount of LP fees that `claim_fees` would currently pay out to
+    /// this account.
+    ///
+    /// The `claim_fees` handler isn't part of this crate, so this formula is inferred from the
+    /// account fields rather than mirrored from the program. The pool's
+    /// `total_accumulated_lp_reward` only ever increases, and each account records its value as
+    /// `last_observed_tap` whenever fees are claimed, so the rewards accrued since then are
+    /// assumed to be shared pro rata by deposit:
+    ///
+    /// `(total_accumulated_lp_reward - last_observed_tap) * amount_deposited / total_liquidity_deposits`
+    ///
+    /// rounded down. Use it as a preview, not as a guarantee of the amount claimed.
+    pub fn estimated_claimable_fees(&self, pool: &SSLPool) -> u64 {
+        if pool.total_liquidity_deposits == 0 {
+            return 0;
+        }
+        let accrued = pool
+            .total_accumulated_lp_reward
+            .saturating_sub(self.last_observed_tap) as u128;
+        let claimable =
+            accrued * self.amount_deposited as u128 / pool.total_liquidity_deposits as u128;
+        u64::try_from(claimable).unwrap_or(u64::MAX)
+    }
 }
 
 impl PDAIdentifier for LiquidityAccount {
@@ -101,3 +125,71 @@ impl PDAIdentifier for LiquidityAccount {
         &crate::ID
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(total_accumulated_lp_reward: u64, total_liquidity_deposits: u64) -> SSLPool {
+        SSLPool {
+            total_accumulated_lp_reward,
+            total_liquidity_deposits,
+            ..Default::default()
+        }
+    }
+
+    fn liquidity_account(amount_deposited: u64, last_observed_tap: u64) -> LiquidityAccount {
+        LiquidityAccount {
+            amount_deposited,
+            last_observed_tap,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn estimated_claimable_fees_are_shared_by_deposit() {
+        // 1_000 accrued since the last claim, the account owns a quarter of deposits
+        let pool = pool(1_500, 4_000_000);
+        assert_eq!(
+            liquidity_account(1_000_000, 500).estimated_claimable_fees(&pool),
+            250
+        );
+        // A sole depositor gets everything
+        assert_eq!(
+            liquidity_account(4_000_000, 500).estimated_claimable_fees(&pool),
+            1_000
+        );
+        // Nothing accrued since the last claim
+        assert_eq!(
+            liquidity_account(1_000_000, 1_500).estimated_claimable_fees(&pool),
+            0
+        );
+    }
+
+    #[test]
+    fn estimated_claimable_fees_round_down() {
+        let pool = pool(10, 3);
+        assert_eq!(liquidity_account(1, 0).estimated_claimable_fees(&pool), 3);
+    }
+
+    #[test]
+    fn estimated_claimable_fees_without_deposits() {
+        assert_eq!(
+            liquidity_account(0, 0).estimated_claimable_fees(&pool(1_000, 0)),
+            0
+        );
+        assert_eq!(
+            liquidity_account(0, 0).estimated_claimable_fees(&pool(1_000, 10)),
+            0
+        );
+    }
+
+    #[test]
+    fn estimated_claimable_fees_do_not_overflow() {
+        let pool = pool(u64::MAX, u64::MAX);
+        assert_eq!(
+            liquidity_account(u64::MAX, 0).estimated_claimable_fees(&pool),
+            u64::MAX
+        );
+    }
+}
@@ -19,6 +19,56 @@ pub struct HistoricalDecimal {
     pub _pad0: [u8; 4],
 }
 
+impl HistoricalDecimal {
+    pub fn new(num: i64, scale: u32) -> Self {
+        Self {
+            num,
+            scale,
+            _pad0: [0; 4],
+        }
+    }
+
+    /// Lossy conversion: precision is reduced, rounding half to even, as needed for the
+    /// mantissa to fit in an [i64]. Fails only if the integer part doesn't fit in an [i64].
+    /// Use [HistoricalDecimal::try_from] to reject any loss of precision.
+    pub fn from_decimal_rounded(
+        value: Decimal,
+    ) -> std::result::Result<Self, HistoricalDecimalOutOfRange> {
+        let mut rounded = value.normalize();
+        while i64::try_from(rounded.mantissa()).is_err() && rounded.scale() > 0 {
+            rounded = rounded.round_dp(rounded.scale() - 1);
+        }
+        let num =
+            i64::try_from(rounded.mantissa()).map_err(|_| HistoricalDecimalOutOfRange(value))?;
+        Ok(Self::new(num, rounded.scale()))
+    }
+}
+
+/// Exact conversion. Trailing zeroes are dropped, and fails if the mantissa
+/// still doesn't fit in an [i64].
+impl TryFrom<Decimal> for HistoricalDecimal {
+    type Error = HistoricalDecimalOutOfRange;
+
+    fn try_from(value: Decimal) -> std::result::Result<Self, Self::Error> {
+        let normalized = value.normalize();
+        let num =
+            i64::try_from(normalized.mantissa()).map_err(|_| HistoricalDecimalOutOfRange(value))?;
+        Ok(Self::new(num, normalized.scale()))
+    }
+}
+
+/// A [Decimal] can't be represented as a [HistoricalDecimal].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct HistoricalDecimalOutOfRange(pub Decimal);
+
+impl std::fmt::Display for HistoricalDecimalOutOfRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} doesn't fit in a historical decimal", self.0)
+    }
+}
+
+impl std::error::Error for HistoricalDecimalOutOfRange {}
+
 impl Into<Decimal> for HistoricalDecimal {
     fn into(self) -> Decimal {
         Decimal::new(self.num, self.scale)
@@ -66,6 +66,11 @@ impl Display for OraclePriceHistory {
             "Maximum Price Slot Staleness: {}",
             self.max_slot_price_staleness
         )?;
+        writeln!(f, "Number of Updates: {}", self.num_updates)?;
+        match self.latest_price() {
+            Ok(latest_price) => writeln!(f, "Latest {}", latest_price)?,
+            Err(_) => writeln!(f, "Latest Price: None")?,
+        }
 
         let price_iter = AccountHistoryIterator::from(self);
         for (idx, historical_price) in price_iter.enumerate() {
@@ -132,6 +137,25 @@ impl OraclePriceHistory {
         price_history
     }
 
+    /// Create an instance from prices recorded at arbitrary slots and scales.
+    /// Entries are pushed in order, so the last entry is the most recent.
+    /// Unlike [OraclePriceHistory::from_test_data], `num_updates` reflects
+    /// the number of entries given. For unit testing.
+    #[cfg(feature = "no-entrypoint")]
+    pub fn from_price_slot_pairs(data: Vec<(rust_decimal::Decimal, u64)>) -> Self {
+        let mut price_history = Self {
+            max_slot_price_staleness: u8::MAX,
+            ..Self::default_pyth()
+        };
+        data.into_iter().for_each(|(price, slot)| {
+            price_history.push(HistoricalPrice {
+                price: HistoricalDecimal::try_from(price).expect("price fits a HistoricalDecimal"),
+                slot,
+            })
+        });
+        price_history
+    }
+
     /// Calculate an address based on admin and oracle address.
     pub fn address(pool_registry: &Pubkey, oracle_address: &Pubkey) -> Pubkey {
         Self::get_address(&[pool_registry.as_ref(), oracle_address.as_ref()])
@@ -171,7 +195,8 @@ impl OraclePriceHistory {
         Ok(price)
     }
 
-    /// Mean and Standard deviation
+    /// Mean and Standard deviation of the ratio of this (output token) history's prices to
+    /// `input_token_history`'s, i.e. the price of the output token in input tokens.
     /// NOTE: This does not check for price staleness. You must explicitly call
     /// the [HistoricalPrice::ensure_recency] method on a [HistoricalPrice] instance.
     pub fn bollinger_band(
@@ -210,8 +235,15 @@ impl OraclePriceHistory {
         // and iterate over the remaining values, calculating mean.
         // From the rest of the elements,
         // calculate the mean, and gather the items for variance calculation.
+        let mut num_items = 0;
         for (idx, elem) in iterator {
-            let price = Into::<f64>::into(elem.0.price) / Into::<f64>::into(elem.1.price);
+            num_items = idx + 1;
+            let input_token_price = Into::<f64>::into(elem.1.price);
+            // A single bad oracle entry would otherwise poison the mean and std.
+            if input_token_price == 0.0 || !input_token_price.is_finite() {
+                return err!(SSLV2Error::MathError);
+            }
+            let price = Into::<f64>::into(elem.0.price) / input_token_price;
             if idx < mean_window {
                 #[cfg(feature = "debug-msg")]
                 msg!("(mean) next price: {}", price);
@@ -222,6 +254,11 @@ impl OraclePriceHistory {
                 std_items.push(price);
             }
         }
+        // Either history can hold fewer prices than it has updates, once it wraps around.
+        // Don't let the zip silently average over a shorter window.
+        if num_items < mean_window.max(std_window) {
+            return err!(SSLV2Error::EmaOrStdWindowTooLarge);
+        }
 
         // Standard deviation
         let std_mean = std_sum / std_window as f64;
@@ -233,6 +270,9 @@ impl OraclePriceHistory {
         let variance = variance / std_window as f64;
         let std = variance.sqrt();
         let mean = mean_sum / mean_window as f64;
+        if !mean.is_finite() || !std.is_finite() {
+            return err!(SSLV2Error::MathError);
+        }
 
         Ok(BollingerBand { mean, std })
     }
@@ -290,6 +330,7 @@ impl<'data> From<&'data OraclePriceHistory> for AccountHistoryIterator<'data> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rust_decimal::Decimal;
 
     /// Verify manual implementation of `AccountSerialize`
     #[test]
@@ -321,6 +362,69 @@ mod tests {
         assert_eq!(*latest_price, price);
     }
 
+    #[test]
+    fn historical_decimal_round_trips_decimal() {
+        let decimal = Decimal::new(123_456, 3);
+        let historical = HistoricalDecimal::try_from(decimal).unwrap();
+        assert_eq!(historical, HistoricalDecimal::new(123_456, 3));
+        let round_tripped: Decimal = historical.into();
+        assert_eq!(round_tripped, decimal);
+        // Trailing zeroes are dropped
+        assert_eq!(
+            HistoricalDecimal::try_from(Decimal::new(1_500, 3)).unwrap(),
+            HistoricalDecimal::new(15, 1)
+        );
+    }
+
+    #[test]
+    fn historical_decimal_rejects_or_rounds_lost_precision() {
+        let precise = Decimal::from_i128_with_scale(12_345_678_901_234_567_890_123, 22);
+        assert_eq!(
+            HistoricalDecimal::try_from(precise),
+            Err(HistoricalDecimalOutOfRange(precise))
+        );
+        // Precision is reduced to fit an i64 mantissa
+        let historical = HistoricalDecimal::from_decimal_rounded(precise).unwrap();
+        assert!(historical.scale < 22);
+        let reduced: Decimal = historical.into();
+        assert_eq!(reduced.round_dp(10), precise.round_dp(10));
+
+        // Integer parts beyond an i64 are rejected rather than clamped
+        let huge = Decimal::from(i64::MAX) + Decimal::ONE;
+        assert!(HistoricalDecimal::try_from(huge).is_err());
+        assert!(HistoricalDecimal::from_decimal_rounded(huge).is_err());
+        assert!(HistoricalDecimal::from_decimal_rounded(-huge - Decimal::ONE).is_err());
+        assert_eq!(
+            HistoricalDecimal::try_from(Decimal::from(i64::MIN)).unwrap(),
+            HistoricalDecimal::new(i64::MIN, 0)
+        );
+    }
+
+    #[cfg(feature = "no-entrypoint")]
+    #[test]
+    fn from_price_slot_pairs_keeps_slots_and_scales() {
+        let price_history = OraclePriceHistory::from_price_slot_pairs(vec![
+            (Decimal::new(2_000, 2), 10),
+            (Decimal::new(2_100_000, 5), 15),
+            (Decimal::new(22, 0), 40),
+        ]);
+        assert_eq!(price_history.num_updates, 3);
+        let latest_price = price_history.latest_price().unwrap();
+        assert_eq!(latest_price.slot, 40);
+        assert_eq!(latest_price.price, HistoricalDecimal::new(22, 0));
+        let slots = AccountHistoryIterator::from(&price_history)
+            .map(|p| p.slot)
+            .collect::<Vec<_>>();
+        assert_eq!(slots, vec![40, 15, 10]);
+        let prices = AccountHistoryIterator::from(&price_history)
+            .map(|p| p.price.into())
+            .collect::<Vec<Decimal>>();
+        assert_eq!(
+            prices,
+            vec![Decimal::from(22), Decimal::from(21), Decimal::from(20)]
+        );
+    }
+
     #[test]
     fn gets_most_recent_index() {
         let mut price_history = OraclePriceHistory::default();
@@ -402,4 +506,159 @@ mod tests {
         assert_eq!(1, iterated_prices[1].0.slot);
         assert_eq!(3, iterated_prices[1].1.slot);
     }
+
+    /// Push random numbers of prices, including counts around the buffer size,
+    /// and check that iteration yields the retained prices from newest to oldest.
+    #[test]
+    fn iteration_wraps_around() {
+        const N: u64 = NUM_HISTORICAL_PRICE_ENTRIES as u64;
+        // xorshift64, so the test is reproducible without extra dependencies
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        let mut next_random = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        let counts = [0, 1, N - 1, N, N + 1, 2 * N - 1, 2 * N, 2 * N + 1]
+            .into_iter()
+            .chain((0..64).map(|_| next_random() % (4 * N)));
+        for num_updates in counts {
+            let mut price_history = OraclePriceHistory::default();
+            for slot in 1..=num_updates {
+                price_history.push(HistoricalPrice {
+                    price: HistoricalDecimal::new(slot as i64, 2),
+                    slot,
+                });
+            }
+            let slots = AccountHistoryIterator::from(&price_history)
+                .map(|p| p.slot)
+                .collect::<Vec<_>>();
+            assert_eq!(
+                slots.len() as u64,
+                num_updates.min(N),
+                "{num_updates} updates"
+            );
+            assert!(
+                slots.windows(2).all(|pair| pair[0] > pair[1]),
+                "{num_updates} updates"
+            );
+            if num_updates > 0 {
+                assert_eq!(slots[0], num_updates);
+                assert_eq!(*slots.last().unwrap(), num_updates - slots.len() as u64 + 1);
+            }
+        }
+    }
+
+    #[test]
+    fn bollinger_band_rejects_window_beyond_retained_prices() {
+        let history = |num_updates: u64| {
+            let mut price_history = OraclePriceHistory::default();
+            for slot in 1..=num_updates {
+                price_history.push(HistoricalPrice {
+                    price: HistoricalDecimal::new(100 + slot as i64 % 3, 2),
+                    slot,
+                });
+            }
+            price_history
+        };
+        let output_token_history = history(300);
+        let input_token_history = history(257);
+        let window = NUM_HISTORICAL_PRICE_ENTRIES;
+        assert!(output_token_history
+            .bollinger_band(window, window, &input_token_history)
+            .is_ok());
+        // Both accounts have enough updates, but only retain the latest prices
+        let result = output_token_history.bollinger_band(window + 1, window, &input_token_history);
+        assert_eq!(
+            result.unwrap_err(),
+            error!(SSLV2Error::EmaOrStdWindowTooLarge)
+        );
+        let result = output_token_history.bollinger_band(window, window + 1, &input_token_history);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn bollinger_band_is_input_tokens_per_output_token() {
+        let mut output_token_history = OraclePriceHistory::default();
+        let mut input_token_history = OraclePriceHistory::default();
+        for slot in 1..=2 {
+            output_token_history.push(HistoricalPrice {
+                price: HistoricalDecimal::new(20, 0),
+                slot,
+            });
+            input_token_history.push(HistoricalPrice {
+                price: HistoricalDecimal::new(1, 0),
+                slot,
+            });
+        }
+        let bband = output_token_history
+            .bollinger_band(2, 2, &input_token_history)
+            .unwrap();
+        assert_eq!(bband.mean, 20.0);
+        assert_eq!(bband.std, 0.0);
+    }
+
+    #[test]
+    fn bollinger_band_rejects_zero_input_token_price() {
+        let mut output_token_history = OraclePriceHistory::default();
+        let mut input_token_history = OraclePriceHistory::default();
+        for slot in 1..=3 {
+            output_token_history.push(HistoricalPrice {
+                price: HistoricalDecimal::new(2000, 2),
+                slot,
+            });
+            input_token_history.push(HistoricalPrice {
+                price: HistoricalDecimal::new(if slot == 2 { 0 } else { 100 }, 2),
+                slot,
+            });
+        }
+        assert!(output_token_history
+            .bollinger_band(1, 1, &input_token_history)
+            .is_ok());
+        assert_eq!(
+            output_token_history
+                .bollinger_band(3, 3, &input_token_history)
+                .unwrap_err(),
+            error!(SSLV2Error::MathError)
+        );
+    }
+
+    #[test]
+    fn bollinger_band_rejects_non_finite_results() {
+        let mut output_token_history = OraclePriceHistory::default();
+        let mut input_token_history = OraclePriceHistory::default();
+        for slot in 1..=2 {
+            output_token_history.push(HistoricalPrice {
+                price: HistoricalDecimal::new(if slot == 1 { i64::MAX } else { 1 }, 0),
+                slot,
+            });
+            input_token_history.push(HistoricalPrice {
+                price: HistoricalDecimal::new(1, 150),
+                slot,
+            });
+        }
+        // The mean of the ratios is finite, but squaring their deviation overflows.
+        assert_eq!(
+            output_token_history
+                .bollinger_band(2, 2, &input_token_history)
+                .unwrap_err(),
+            error!(SSLV2Error::MathError)
+        );
+
+        // A ratio that overflows by itself
+        let mut input_token_history = OraclePriceHistory::default();
+        for slot in 1..=2 {
+            input_token_history.push(HistoricalPrice {
+                price: HistoricalDecimal::new(1, 300),
+                slot,
+            });
+        }
+        assert_eq!(
+            output_token_history
+                .bollinger_band(2, 2, &input_token_history)
+                .unwrap_err(),
+            error!(SSLV2Error::MathError)
+        );
+    }
 }
@@ -129,6 +129,11 @@ impl Pair {
         Self::get_address(&[pool_registry.key().as_ref(), m1.as_ref(), m2.as_ref()])
     }
 
+    /// Whether `address` is the PDA derived from this pair's pool registry and mints.
+    pub fn verify_address(&self, address: Pubkey) -> bool {
+        Self::address(self.pool_registry, self.mints.0, self.mints.1) == address
+    }
+
     /// Anchor account initialization is done with a macro attribute
     /// in an `Accounts` context. This initialization function should be
     /// executed in the instruction body.
@@ -172,6 +177,28 @@ impl Pair {
         }
     }
 
+    /// The fee collector associated with `mint`, or `None` if `mint` is not in the pair.
+    pub fn fee_collector_for(&self, mint: Pubkey) -> Option<Pubkey> {
+        if mint == self.mints.0 {
+            Some(self.fee_collector.0)
+        } else if mint == self.mints.1 {
+            Some(self.fee_collector.1)
+        } else {
+            None
+        }
+    }
+
+    /// The fee rate in BPS associated with `mint`, or `None` if `mint` is not in the pair.
+    pub fn fee_rate_for(&self, mint: Pubkey) -> Option<u16> {
+        if mint == self.mints.0 {
+            Some(self.fee_rates.0)
+        } else if mint == self.mints.1 {
+            Some(self.fee_rates.1)
+        } else {
+            None
+        }
+    }
+
     pub fn historical_volume(&self) -> u128 {
         u128_from_bytes(&self.total_historical_volume)
     }
@@ -208,3 +235,54 @@ pub enum SwapIxMintOrdering {
     /// When pair.mints = (mint_out, mint_in)
     OutIn,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pair() -> Pair {
+        let (mint_one, mint_two) =
+            Pair::normalize_mint_order(Pubkey::new_unique(), Pubkey::new_unique());
+        let mut pair = Pair::default();
+        pair.initialize(
+            Pubkey::new_unique(),
+            mint_one,
+            mint_two,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            10,
+            20,
+        );
+        pair
+    }
+
+    #[test]
+    fn verify_address() {
+        let pair = pair();
+        let address = Pair::address(pair.pool_registry, pair.mints.1, pair.mints.0);
+        assert!(pair.verify_address(address));
+        assert!(!pair.verify_address(Pubkey::new_unique()));
+    }
+
+    #[test]
+    fn fee_collector_for_mint() {
+        let pair = pair();
+        assert_eq!(
+            pair.fee_collector_for(pair.mints.0),
+            Some(pair.fee_collector.0)
+        );
+        assert_eq!(
+            pair.fee_collector_for(pair.mints.1),
+            Some(pair.fee_collector.1)
+        );
+        assert_eq!(pair.fee_collector_for(Pubkey::new_unique()), None);
+    }
+
+    #[test]
+    fn fee_rate_for_mint() {
+        let pair = pair();
+        assert_eq!(pair.fee_rate_for(pair.mints.0), Some(10));
+        assert_eq!(pair.fee_rate_for(pair.mints.1), Some(20));
+        assert_eq!(pair.fee_rate_for(Pubkey::new_unique()), None);
+    }
+}
@@ -1,6 +1,5 @@
 use anchor_lang::prelude::*;
 use bytemuck::{Pod, Zeroable};
-#[cfg(feature = "no-entrypoint")]
 use std::fmt::{Display, Formatter};
 
 /// Identical to [SSLMathParams] except that every field is an `Option` type,
@@ -17,6 +16,18 @@ pub struct SSLMathConfig {
     pub latest_price_weight: Option<u16>,
 }
 
+impl SSLMathConfig {
+    /// Whether this config would leave every math parameter unchanged.
+    pub fn is_empty(&self) -> bool {
+        self.mean_window.is_none()
+            && self.std_window.is_none()
+            && self.fixed_price_distance.is_none()
+            && self.minimum_price_distance.is_none()
+            && self.std_weight.is_none()
+            && self.latest_price_weight.is_none()
+    }
+}
+
 /// The set of configurable parameters for each SSL pool.
 /// These control price calculation, and thresholds for various conditionals
 /// that may force the failure of an attempted swap.
@@ -44,7 +55,9 @@ pub struct SSLMathParams {
     /// A minimum distance from the latest oracle price expressed in BPS.
     pub minimum_price_distance: u16,
 
-    /// Previously stored max_pool_token_ratio
+    /// Previously stored max_pool_token_ratio, which is now configured per pair of asset types
+    /// on the pool registry. The program ignores this field, but it must be zero in new params,
+    /// see [SSLMathParams::validate]. Pools created before the change may still store a value.
     pub _deprecated: u16,
 
     /// A weight that controls the price influence ratio between
@@ -77,6 +90,14 @@ pub struct SSLMathParams {
 }
 
 impl SSLMathParams {
+    /// Params for a fresh pool: the fields set in `config`, with every other field zeroed
+    /// as in [SSLMathParams::default].
+    pub fn from_config(config: SSLMathConfig) -> Self {
+        let mut params = Self::default();
+        params.configure(config);
+        params
+    }
+
     pub fn configure(&mut self, config: SSLMathConfig) {
         if let Some(val) = config.mean_window {
             self.mean_window = val;
@@ -97,8 +118,87 @@ impl SSLMathParams {
             self.latest_price_weight = val;
         }
     }
+
+    /// Check params before they are used to create or configure a pool.
+    /// Rejects a nonzero [SSLMathParams::_deprecated], so that hand-written params
+    /// can't appear to set the old max_pool_token_ratio.
+    pub fn validate(&self) -> std::result::Result<(), DeprecatedMathParam> {
+        if self._deprecated != 0 {
+            return Err(DeprecatedMathParam(self._deprecated));
+        }
+        Ok(())
+    }
+
+    /// The exact bytes of these params as stored in an on-chain [SSLPool](super::SSLPool),
+    /// padding included.
+    pub fn to_onchain_bytes(&self) -> [u8; 56] {
+        bytemuck::cast(*self)
+    }
+
+    /// Inverse of [SSLMathParams::to_onchain_bytes].
+    pub fn from_onchain_bytes(bytes: &[u8; 56]) -> Self {
+        bytemuck::cast(*bytes)
+    }
+
+    /// The fields that [SSLMathParams::configure] would change, as `(field, old, new)`.
+    pub fn diff(&self, config: &SSLMathConfig) -> Vec<(&'static str, String, String)> {
+        let mut new = *self;
+        new.configure(*config);
+        [
+            (
+                "mean_window",
+                self.mean_window.to_string(),
+                new.mean_window.to_string(),
+            ),
+            (
+                "std_window",
+                self.std_window.to_string(),
+                new.std_window.to_string(),
+            ),
+            (
+                "fixed_price_distance",
+                self.fixed_price_distance.to_string(),
+                new.fixed_price_distance.to_string(),
+            ),
+            (
+                "minimum_price_distance",
+                self.minimum_price_distance.to_string(),
+                new.minimum_price_distance.to_string(),
+            ),
+            (
+                "std_weight",
+                self.std_weight.to_string(),
+                new.std_weight.to_string(),
+            ),
+            (
+                "latest_price_weight",
+                self.latest_price_weight.to_string(),
+                new.latest_price_weight.to_string(),
+            ),
+        ]
+        .into_iter()
+        .filter(|(_, old, new)| old != new)
+        .collect()
+    }
+}
+
+/// [SSLMathParams::_deprecated] holds a value, see [SSLMathParams::validate].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DeprecatedMathParam(pub u16);
+
+impl Display for DeprecatedMathParam {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "The deprecated max pool token ratio is set to {}, it must be zero. \
+            Configure max pool token ratios on the pool registry instead",
+            self.0
+        )
+    }
 }
 
+impl std::error::Error for DeprecatedMathParam {}
+
 #[cfg_attr(feature = "python", pyo3::pymethods)]
 impl SSLMathParams {
     #[cfg(feature = "python")]
@@ -161,3 +261,133 @@ pub fn default_padding4() -> [u8; 4] {
 pub fn default_padding32() -> [u8; 32] {
     [0u8; 32]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_config_is_a_no_op() {
+        let config = SSLMathConfig::default();
+        assert!(config.is_empty());
+        let mut params = SSLMathParams {
+            mean_window: 10,
+            std_window: 20,
+            ..Default::default()
+        };
+        let before = params;
+        params.configure(config);
+        assert_eq!(params, before);
+    }
+
+    #[test]
+    fn validate_rejects_deprecated_field() {
+        let mut params = SSLMathParams {
+            mean_window: 21,
+            std_window: 106,
+            ..Default::default()
+        };
+        assert_eq!(params.validate(), Ok(()));
+        params._deprecated = 1_038;
+        assert_eq!(params.validate(), Err(DeprecatedMathParam(1_038)));
+    }
+
+    #[test]
+    fn from_config_zeroes_unset_fields() {
+        let params = SSLMathParams::from_config(SSLMathConfig {
+            mean_window: Some(21),
+            std_weight: Some(5_194),
+            ..Default::default()
+        });
+        assert_eq!(
+            params,
+            SSLMathParams {
+                mean_window: 21,
+                std_weight: 5_194,
+                ..Default::default()
+            }
+        );
+        assert_eq!(params.std_window, 0);
+        assert_eq!(params.latest_price_weight, 0);
+        assert_eq!(
+            SSLMathParams::from_config(SSLMathConfig::default()),
+            SSLMathParams::default()
+        );
+        assert_eq!(params.validate(), Ok(()));
+    }
+
+    #[test]
+    fn diff_lists_only_changed_fields() {
+        let params = SSLMathParams {
+            mean_window: 10,
+            std_window: 20,
+            std_weight: 5_000,
+            ..Default::default()
+        };
+        let config = SSLMathConfig {
+            mean_window: Some(12),
+            // Set, but to the current value
+            std_window: Some(20),
+            latest_price_weight: Some(9_000),
+            ..Default::default()
+        };
+        assert_eq!(
+            params.diff(&config),
+            vec![
+                ("mean_window", "10".to_string(), "12".to_string()),
+                ("latest_price_weight", "0".to_string(), "9000".to_string()),
+            ]
+        );
+        assert!(params.diff(&SSLMathConfig::default()).is_empty());
+    }
+
+    #[test]
+    fn any_field_makes_config_non_empty() {
+        let config = SSLMathConfig {
+            latest_price_weight: Some(0),
+            ..Default::default()
+        };
+        assert!(!config.is_empty());
+    }
+
+    #[test]
+    fn onchain_bytes_round_trip() {
+        let mut params = SSLMathParams {
+            mean_window: 10,
+            std_window: 20,
+            fixed_price_distance: 300,
+            minimum_price_distance: 40,
+            latest_price_weight: 5_000,
+            std_weight: 60_000,
+            ..Default::default()
+        };
+        params._pad0 = [1; 6];
+        params._space = [2; 32];
+        let bytes = params.to_onchain_bytes();
+        assert_eq!(SSLMathParams::from_onchain_bytes(&bytes), params);
+        assert_eq!(bytes.to_vec(), params.try_to_vec().unwrap());
+        assert_eq!(&bytes[..4], &[10, 20, 44, 1]);
+        assert_eq!(&bytes[16..20], &60_000u32.to_le_bytes());
+    }
+
+    #[cfg(feature = "no-entrypoint")]
+    #[test]
+    fn onchain_bytes_match_json() {
+        let params: SSLMathParams = serde_json::from_str(
+            r#"{
+                "mean_window": 10,
+                "std_window": 20,
+                "fixed_price_distance": 300,
+                "minimum_price_distance": 40,
+                "_deprecated": 0,
+                "latest_price_weight": 5000,
+                "std_weight": 60000
+            }"#,
+        )
+        .unwrap();
+        let bytes = params.to_onchain_bytes();
+        assert_eq!(SSLMathParams::from_onchain_bytes(&bytes), params);
+        assert_eq!(bytes.to_vec(), params.try_to_vec().unwrap());
+        assert!(bytes[10..16].iter().chain(&bytes[20..]).all(|b| *b == 0));
+    }
+}
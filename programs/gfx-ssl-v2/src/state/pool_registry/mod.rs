@@ -11,13 +11,22 @@ use std::io::Write;
 
 pub use crate::state::liquidity_account::LiquidityAccount;
 use crate::{token_ratio_category::MaxPoolTokenRatio, SSLV2Error};
-pub use math_params::{SSLMathConfig, SSLMathParams};
+pub use math_params::{DeprecatedMathParam, SSLMathConfig, SSLMathParams};
 pub use ssl_pool::{AssetType, SSLPool, SSLPoolStatus};
 
 /// We need to enforce a maximum number of pools per admin
 /// because of Solana's on-chain compute limitations.
 pub const MAX_SSL_POOLS_PER_ADMIN: usize = 32;
 
+/// Current value of [PoolRegistry::layout_version].
+/// Must be bumped whenever the layout of [PoolRegistry] or [SSLPool] changes.
+/// Version 0 is the reserved, zeroed byte of accounts that don't record a version,
+/// so it doesn't identify a layout.
+pub const POOL_REGISTRY_LAYOUT_VERSION: u8 = 1;
+
+/// Version read from accounts that don't record a layout version.
+pub const POOL_REGISTRY_UNVERSIONED: u8 = 0;
+
 /// A global registry that stores an exhaustive list of all SSL pools owned by a specific admin.
 ///
 /// All SSL pools owned by the same admin exist under the same swappable domain.
@@ -38,7 +47,11 @@ pub struct PoolRegistry {
     pub num_entries: u32,
     pub _pad1: [u8; 4],
     pub categorical_pool_token_ratios: [u16; 16],
-    pub _space: [u8; 96],
+    /// See [POOL_REGISTRY_LAYOUT_VERSION]. This byte was taken from the reserved space,
+    /// and the deployed program has to agree to write it before clients can rely on it.
+    /// Until then every account reads [POOL_REGISTRY_UNVERSIONED].
+    pub layout_version: u8,
+    pub _space: [u8; 95],
     /// A list of oracles whose mints are offered as SSL pools under the domain of a given
     /// admin.
     pub entries: [SSLPool; MAX_SSL_POOLS_PER_ADMIN],
@@ -83,7 +96,7 @@ impl Display for PoolRegistry {
         for (idx, entry) in self
             .entries
             .iter()
-            .filter(|pool| **pool != SSLPool::default())
+            .filter(|pool| !pool.is_empty())
             .enumerate()
         {
             writeln!(f, "SSL Pool: {}", idx)?;
@@ -116,6 +129,25 @@ impl PoolRegistry {
         self.seed = admin;
         self.suspend_admin = admin;
         self.bump = bump;
+        self.layout_version = POOL_REGISTRY_LAYOUT_VERSION;
+    }
+
+    /// Read the layout version from raw account data, without casting the whole account.
+    /// Fails if the data doesn't start with the [PoolRegistry] discriminator
+    /// or has the wrong size.
+    ///
+    /// Clients can use this to reject accounts written by a different version of the program.
+    /// [POOL_REGISTRY_UNVERSIONED] means the account doesn't record its layout.
+    pub fn layout_version(data: &[u8]) -> Result<u8> {
+        if data.len() < 8 || data[..8] != Self::discriminator() {
+            return err!(ErrorCode::AccountDiscriminatorMismatch);
+        }
+        if data.len() != 8 + std::mem::size_of::<Self>() {
+            return err!(ErrorCode::AccountDidNotDeserialize);
+        }
+        // Account data isn't necessarily aligned for a cast
+        let pool_registry: Self = bytemuck::pod_read_unaligned(&data[8..]);
+        Ok(pool_registry.layout_version)
     }
 
     /// Fetch an immutable ref SSL pool from the array of pools.
@@ -145,3 +177,63 @@ pub struct PoolRegistryConfig {
     pub new_suspend_admin: Option<Pubkey>,
     pub max_pool_token_ratios: Vec<MaxPoolTokenRatio>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layout_version_is_read_from_account_data() {
+        let mut pool_registry = PoolRegistry::default();
+        pool_registry.initialize(Pubkey::new_unique(), 255);
+        pool_registry.layout_version = 7;
+        let mut data = vec![];
+        pool_registry.try_serialize(&mut data).unwrap();
+        assert_eq!(PoolRegistry::layout_version(&data).unwrap(), 7);
+    }
+
+    #[test]
+    fn initialize_records_layout_version() {
+        let mut data = vec![];
+        PoolRegistry::default().try_serialize(&mut data).unwrap();
+        assert_eq!(
+            PoolRegistry::layout_version(&data).unwrap(),
+            POOL_REGISTRY_UNVERSIONED
+        );
+
+        let mut pool_registry = PoolRegistry::default();
+        pool_registry.initialize(Pubkey::new_unique(), 255);
+        let mut data = vec![];
+        pool_registry.try_serialize(&mut data).unwrap();
+        assert_eq!(
+            PoolRegistry::layout_version(&data).unwrap(),
+            POOL_REGISTRY_LAYOUT_VERSION
+        );
+        assert_ne!(POOL_REGISTRY_LAYOUT_VERSION, POOL_REGISTRY_UNVERSIONED);
+    }
+
+    #[test]
+    fn layout_version_rejects_other_accounts() {
+        let mut data = vec![];
+        PoolRegistry::default().try_serialize(&mut data).unwrap();
+        assert!(PoolRegistry::layout_version(&data[..data.len() - 1]).is_err());
+        data[0] ^= 1;
+        assert!(PoolRegistry::layout_version(&data).is_err());
+    }
+
+    #[test]
+    fn empty_pool_ignores_padding() {
+        let mut pool = SSLPool::default();
+        pool._pad0 = [1; 6];
+        pool._pad1 = [2; 6];
+        pool._space = [3; 64];
+        assert!(pool.is_empty());
+        assert_ne!(pool, SSLPool::default());
+
+        pool.mint = Pubkey::new_unique();
+        assert!(!pool.is_empty());
+        pool.mint = Pubkey::default();
+        pool.status = SSLPoolStatus::Active.into();
+        assert!(!pool.is_empty());
+    }
+}
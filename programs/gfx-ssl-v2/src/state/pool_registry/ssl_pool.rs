@@ -269,6 +269,12 @@ impl SSLPool {
         AssetType::from(self.asset_type)
     }
 
+    /// Whether this [PoolRegistry] entry holds no pool. Unlike comparing against
+    /// [SSLPool::default], this ignores padding and any other leftover bytes.
+    pub fn is_empty(&self) -> bool {
+        self.status() == SSLPoolStatus::Uninitialized && self.mint == Pubkey::default()
+    }
+
     /// This method is used during iteration over the [PoolRegistry]'s entries,
     /// to determine whether we can simply skip over a given entry.
     pub fn is_initialized(&self) -> bool {
@@ -6,6 +6,7 @@ use rust_decimal::Decimal;
 /// Native == Lamports
 /// "UI" == SOL
 pub mod token_amount {
+    use crate::SSLV2Error;
     use num_traits::ToPrimitive;
     use rust_decimal::Decimal;
 
@@ -19,8 +20,36 @@ pub mod token_amount {
         amount.to_u64().unwrap()
     }
 
+    /// Convert a "UI" [Decimal] to a native u64 amount, rejecting any value
+    /// that cannot be represented exactly.
+    /// You must pass the mint's `decimals` as the scale.
+    ///
+    /// Unlike [to_native], this never truncates. It fails if `ui_amount` is negative,
+    /// has more fractional digits than `decimals`, or overflows a u64.
+    /// Trailing zeroes are not counted as fractional digits.
+    pub fn from_ui(ui_amount: Decimal, decimals: u32) -> Result<u64, SSLV2Error> {
+        let amount = ui_amount.normalize();
+        if amount.scale() > decimals {
+            return Err(SSLV2Error::MathError);
+        }
+        let multiplier = 10i128
+            .checked_pow(decimals - amount.scale())
+            .ok_or(SSLV2Error::MathError)?;
+        let native = amount
+            .mantissa()
+            .checked_mul(multiplier)
+            .ok_or(SSLV2Error::MathError)?;
+        u64::try_from(native).map_err(|_| SSLV2Error::MathError)
+    }
+
     /// Convert a u64 value to a "UI" [Decimal] representation.
     /// You must pass the mint's `decimals` as the scale.
+    ///
+    /// The conversion is exact: the native amount becomes the [Decimal] mantissa
+    /// and only the scale is set, so no rounding or truncation ever occurs.
+    /// Every `u64` fits in the 96-bit mantissa.
+    ///
+    /// Panics if `scale` exceeds the maximum [Decimal] scale of 28.
     pub fn to_ui(amount: u64, scale: u32) -> Decimal {
         let mut amount = Decimal::from(amount);
         amount.set_scale(scale).unwrap();
@@ -30,6 +59,11 @@ pub mod token_amount {
 
     /// Convert a u128 value to a "UI" [Decimal] representation.
     /// You must pass the mint's `decimals` as the scale.
+    ///
+    /// Exact in the same way as [to_ui].
+    ///
+    /// Panics if `amount` does not fit in the 96-bit [Decimal] mantissa,
+    /// or if `scale` exceeds 28.
     pub fn u128_to_ui(amount: u128, scale: u32) -> Decimal {
         let mut amount = Decimal::from(amount);
         amount.set_scale(scale).unwrap();
@@ -54,8 +88,9 @@ pub trait PDAIdentifier {
     }
 }
 
+/// The bytes are not necessarily 16-byte aligned inside account structs.
 pub fn u128_from_bytes(value: &[u8; 16]) -> u128 {
-    *bytemuck::from_bytes::<u128>(&value[..])
+    bytemuck::pod_read_unaligned(value)
 }
 
 /// Convert u16 representing percentage basis-points (BPS) to a [Decimal].
@@ -67,3 +102,113 @@ pub fn u16_to_bps(val: u16) -> Decimal {
 pub fn u32_to_bps(val: u32) -> Decimal {
     Decimal::new(val as i64, 4)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn to_ui_is_exact() {
+        assert_eq!(
+            token_amount::to_ui(1, 9),
+            Decimal::from_str("0.000000001").unwrap()
+        );
+        assert_eq!(
+            token_amount::to_ui(999_999_999, 9),
+            Decimal::from_str("0.999999999").unwrap()
+        );
+        assert_eq!(
+            token_amount::to_ui(1_000_000_000, 9),
+            Decimal::from_str("1").unwrap()
+        );
+        assert_eq!(
+            token_amount::to_ui(u64::MAX, 9),
+            Decimal::from_str("18446744073709.551615").unwrap()
+        );
+        assert_eq!(
+            token_amount::to_ui(u64::MAX, 0),
+            Decimal::from_str("18446744073709551615").unwrap()
+        );
+    }
+
+    #[test]
+    fn to_ui_does_not_round() {
+        // A value with more significant digits than an f64 can represent
+        // must survive the conversion without losing its last digit.
+        let ui = token_amount::to_ui(9_007_199_254_740_993, 9);
+        assert_eq!(ui.to_string(), "9007199.254740993");
+        assert_eq!(token_amount::to_ui(5, 1).to_string(), "0.5");
+        assert_eq!(token_amount::to_ui(15, 1).to_string(), "1.5");
+    }
+
+    #[test]
+    fn u128_to_ui_is_exact() {
+        assert_eq!(
+            token_amount::u128_to_ui(1, 6),
+            Decimal::from_str("0.000001").unwrap()
+        );
+        assert_eq!(
+            token_amount::u128_to_ui(u64::MAX as u128 + 1, 6),
+            Decimal::from_str("18446744073709.551616").unwrap()
+        );
+    }
+
+    #[test]
+    fn from_ui_converts_exactly() {
+        let ui = |s: &str| Decimal::from_str(s).unwrap();
+        assert_eq!(token_amount::from_ui(ui("1.5"), 9), Ok(1_500_000_000));
+        assert_eq!(token_amount::from_ui(ui("0.000000001"), 9), Ok(1));
+        assert_eq!(token_amount::from_ui(ui("0"), 9), Ok(0));
+        assert_eq!(token_amount::from_ui(ui("42"), 0), Ok(42));
+        // Trailing zeroes do not count against the mint's decimals
+        assert_eq!(token_amount::from_ui(ui("1.50"), 1), Ok(15));
+        assert_eq!(
+            token_amount::from_ui(ui("18446744073709.551615"), 9),
+            Ok(u64::MAX)
+        );
+    }
+
+    #[test]
+    fn from_ui_rejects_precision_loss() {
+        let ui = |s: &str| Decimal::from_str(s).unwrap();
+        assert_eq!(
+            token_amount::from_ui(ui("1.0000000001"), 9),
+            Err(SSLV2Error::MathError)
+        );
+        assert_eq!(
+            token_amount::from_ui(ui("0.5"), 0),
+            Err(SSLV2Error::MathError)
+        );
+    }
+
+    #[test]
+    fn from_ui_rejects_overflow_and_negatives() {
+        let ui = |s: &str| Decimal::from_str(s).unwrap();
+        assert_eq!(
+            token_amount::from_ui(ui("18446744073709.551616"), 9),
+            Err(SSLV2Error::MathError)
+        );
+        assert_eq!(
+            token_amount::from_ui(ui("18446744073709551616"), 0),
+            Err(SSLV2Error::MathError)
+        );
+        assert_eq!(
+            token_amount::from_ui(ui("1"), 40),
+            Err(SSLV2Error::MathError)
+        );
+        assert_eq!(
+            token_amount::from_ui(ui("-1"), 9),
+            Err(SSLV2Error::MathError)
+        );
+    }
+
+    #[test]
+    fn to_ui_round_trips_to_native() {
+        for amount in [0, 1, 999_999_999, 1_000_000_000, u64::MAX] {
+            let ui = token_amount::to_ui(amount, 9);
+            assert_eq!(token_amount::to_native(ui, 9), amount);
+            assert_eq!(token_amount::from_ui(ui, 9), Ok(amount));
+        }
+    }
+}
@@ -0,0 +1,66 @@
+//! Anchor account discriminators, for decoding accounts of unknown type.
+//! SSL pools have no discriminator of their own,
+//! they are entries of a [PoolRegistry](crate::state::PoolRegistry).
+
+pub const POOL_REGISTRY_DISCRIMINATOR: [u8; 8] = [113, 149, 124, 60, 130, 240, 64, 157];
+pub const PAIR_DISCRIMINATOR: [u8; 8] = [85, 72, 49, 176, 182, 228, 141, 82];
+pub const ORACLE_PRICE_HISTORY_DISCRIMINATOR: [u8; 8] = [180, 252, 163, 223, 218, 49, 27, 242];
+pub const LIQUIDITY_ACCOUNT_DISCRIMINATOR: [u8; 8] = [190, 123, 167, 176, 248, 51, 215, 26];
+pub const EVENT_EMITTER_DISCRIMINATOR: [u8; 8] = [215, 12, 224, 60, 205, 124, 188, 73];
+
+/// The account types owned by the SSLv2 program.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AccountKind {
+    PoolRegistry,
+    Pair,
+    OraclePriceHistory,
+    LiquidityAccount,
+    EventEmitter,
+}
+
+/// Identify an account type by the first 8 bytes of its data.
+pub fn account_type_for_discriminator(disc: &[u8; 8]) -> Option<AccountKind> {
+    match *disc {
+        POOL_REGISTRY_DISCRIMINATOR => Some(AccountKind::PoolRegistry),
+        PAIR_DISCRIMINATOR => Some(AccountKind::Pair),
+        ORACLE_PRICE_HISTORY_DISCRIMINATOR => Some(AccountKind::OraclePriceHistory),
+        LIQUIDITY_ACCOUNT_DISCRIMINATOR => Some(AccountKind::LiquidityAccount),
+        EVENT_EMITTER_DISCRIMINATOR => Some(AccountKind::EventEmitter),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{EventEmitter, LiquidityAccount, OraclePriceHistory, Pair, PoolRegistry};
+    use anchor_lang::Discriminator;
+
+    #[test]
+    fn constants_match_anchor_discriminators() {
+        assert_eq!(POOL_REGISTRY_DISCRIMINATOR, PoolRegistry::discriminator());
+        assert_eq!(PAIR_DISCRIMINATOR, Pair::discriminator());
+        assert_eq!(
+            ORACLE_PRICE_HISTORY_DISCRIMINATOR,
+            OraclePriceHistory::discriminator()
+        );
+        assert_eq!(
+            LIQUIDITY_ACCOUNT_DISCRIMINATOR,
+            LiquidityAccount::discriminator()
+        );
+        assert_eq!(EVENT_EMITTER_DISCRIMINATOR, EventEmitter::discriminator());
+    }
+
+    #[test]
+    fn looks_up_account_kind() {
+        assert_eq!(
+            account_type_for_discriminator(&PAIR_DISCRIMINATOR),
+            Some(AccountKind::Pair)
+        );
+        assert_eq!(
+            account_type_for_discriminator(&EVENT_EMITTER_DISCRIMINATOR),
+            Some(AccountKind::EventEmitter)
+        );
+        assert_eq!(account_type_for_discriminator(&[0; 8]), None);
+    }
+}
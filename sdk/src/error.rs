@@ -1,31 +1,44 @@
 use solana_sdk::pubkey::Pubkey;
 use thiserror::Error;
 
+/// Errors returned by the SDK. New variants may be added,
+/// so matches on this type need a wildcard arm.
 #[derive(Debug, Clone, Error)]
+#[non_exhaustive]
 pub enum GfxSslSdkError {
     #[error("Account not found: {0}")]
     AccountNotFound(Pubkey),
 
+    #[error("RPC request failed: {0}")]
+    RpcError(String),
+
     #[error("Could not deserialize {0} as type: {1}")]
     DeserializeFailure(Pubkey, String),
 
     #[error("SSL Pool not found in pool registry for mint: {0}")]
     PoolNotFound(Pubkey),
 
-    #[error("Pair does not contain both mints: {0} {1}")]
+    #[error("SSL Pool has an invalid asset type: {0}")]
+    InvalidAssetType(Pubkey),
+
+    /// The given mint isn't one of the pair's two mints, which this holds.
+    #[error("Mint is not one of the pair's mints: {0} {1}")]
     MintNotInPair(Pubkey, Pubkey),
 
-    #[error("Some required accounts are not updated")]
-    RequiredAccountUpdate,
+    #[error("Invalid oracle {0}: {1}")]
+    InvalidOracle(Pubkey, String),
+
+    #[error("Required account is not updated: {0}")]
+    RequiredAccountUpdate(Pubkey),
 
-    #[error("Price history accounts need to be updated")]
-    PriceHistoryUpdateRequired,
+    #[error("Price history account needs to be updated: {0}")]
+    PriceHistoryUpdateRequired(Pubkey),
 
-    #[error("The AMM does not support provided mints")]
-    UnexpectedMints,
+    #[error("The AMM does not support provided mints: {0} {1}")]
+    UnexpectedMints(Pubkey, Pubkey),
 
-    #[error("Tried initializing a GfxAmm instance with inconsistent account data")]
-    InconsistentInitializationData,
+    #[error("Tried initializing a GfxAmm instance with inconsistent account data for pair: {0}")]
+    InconsistentInitializationData(Pubkey),
 }
 
 pub type Result<T> = std::result::Result<T, GfxSslSdkError>;
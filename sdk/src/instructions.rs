@@ -12,7 +12,10 @@ use gfx_ssl_v2_interface::{
     PoolRegistryConfig, SSLMathConfig, SSLMathParams, SSLPool,
 };
 
-use crate::state::get_account_metas_for_swap;
+use crate::{
+    error::{GfxSslSdkError, Result},
+    state::{get_account_metas_for_quote, get_account_metas_for_swap},
+};
 
 pub fn create_pool_registry(admin: Pubkey, funder: Pubkey) -> Instruction {
     let data = gfx_ssl_v2_interface::instruction::CreatePoolRegistry.data();
@@ -298,47 +301,78 @@ pub fn create_pair(
     }
 }
 
-pub fn config_pair(
-    admin: Pubkey,
-    pool_registry: Pubkey,
-    mint_one: Pubkey,
-    mint_two: Pubkey,
-    mint_one_fee_rate: Option<u16>,
-    mint_two_fee_rate: Option<u16>,
-    mint_one_fee_destination: Option<Pubkey>,
-    mint_two_fee_destination: Option<Pubkey>,
-) -> Instruction {
-    let (mint_one_fee_rate, mint_two_fee_rate, mint_one_fee_destination, mint_two_fee_destination) =
-        if (mint_one, mint_two) == Pair::normalize_mint_order(mint_one, mint_two) {
-            (
-                mint_one_fee_rate,
-                mint_two_fee_rate,
-                mint_one_fee_destination,
-                mint_two_fee_destination,
-            )
+/// Fee configuration for [config_pair], keyed by mint rather than by
+/// position in the normalized mint ordering of the [Pair].
+///
+/// The program's `config_pair` only takes one fee rate per mint, and [Pair] stores no
+/// preferred rates, so there is no preferred fee to set alongside [ConfigPairParams::normal_fee].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ConfigPairParams {
+    mints: (Pubkey, Pubkey),
+    fee_rates: (Option<u16>, Option<u16>),
+    fee_destinations: (Option<Pubkey>, Option<Pubkey>),
+}
+
+impl ConfigPairParams {
+    /// Mints may be passed in either order.
+    pub fn new(mint_one: Pubkey, mint_two: Pubkey) -> Self {
+        Self {
+            mints: Pair::normalize_mint_order(mint_one, mint_two),
+            fee_rates: (None, None),
+            fee_destinations: (None, None),
+        }
+    }
+
+    /// Set the fee rate in BPS charged when `mint` is the output mint of a swap.
+    pub fn normal_fee(mut self, mint: Pubkey, bps: u16) -> Result<Self> {
+        if self.is_mint_one(mint)? {
+            self.fee_rates.0 = Some(bps);
         } else {
-            (
-                mint_two_fee_rate,
-                mint_one_fee_rate,
-                mint_two_fee_destination,
-                mint_one_fee_destination,
-            )
-        };
+            self.fee_rates.1 = Some(bps);
+        }
+        Ok(self)
+    }
 
+    /// Set the fee destination for swaps where `mint` is the output mint.
+    pub fn fee_destination(mut self, mint: Pubkey, fee_destination: Pubkey) -> Result<Self> {
+        if self.is_mint_one(mint)? {
+            self.fee_destinations.0 = Some(fee_destination);
+        } else {
+            self.fee_destinations.1 = Some(fee_destination);
+        }
+        Ok(self)
+    }
+
+    pub fn mints(&self) -> (Pubkey, Pubkey) {
+        self.mints
+    }
+
+    fn is_mint_one(&self, mint: Pubkey) -> Result<bool> {
+        if mint == self.mints.0 {
+            Ok(true)
+        } else if mint == self.mints.1 {
+            Ok(false)
+        } else {
+            Err(GfxSslSdkError::MintNotInPair(self.mints.0, self.mints.1))
+        }
+    }
+}
+
+pub fn config_pair(admin: Pubkey, pool_registry: Pubkey, params: ConfigPairParams) -> Instruction {
     let data = gfx_ssl_v2_interface::instruction::ConfigPair {
-        mint_one_fee_rate,
-        mint_two_fee_rate,
+        mint_one_fee_rate: params.fee_rates.0,
+        mint_two_fee_rate: params.fee_rates.1,
     }
     .data();
 
-    let pair = Pair::address(pool_registry, mint_one, mint_two);
+    let pair = Pair::address(pool_registry, params.mints.0, params.mints.1);
 
     let accounts = gfx_ssl_v2_interface::accounts::ConfigPair {
         admin,
         pool_registry,
         pair,
-        mint_one_fee_destination: mint_one_fee_destination.unwrap_or_default(),
-        mint_two_fee_destination: mint_two_fee_destination.unwrap_or_default(),
+        mint_one_fee_destination: params.fee_destinations.0.unwrap_or_default(),
+        mint_two_fee_destination: params.fee_destinations.1.unwrap_or_default(),
     }
     .to_account_metas(None);
 
@@ -385,7 +419,29 @@ pub fn internal_swap(
     mint_two_oracle: Pubkey,
 ) -> Instruction {
     let data = gfx_ssl_v2_interface::instruction::InternalSwap.data();
+    let accounts = internal_swap_account_metas(
+        pool_registry,
+        mint_one,
+        mint_two,
+        mint_one_oracle,
+        mint_two_oracle,
+    );
+    Instruction {
+        program_id: gfx_ssl_v2_interface::ID,
+        accounts,
+        data,
+    }
+}
 
+/// Account metas for an internal swap between the pools of two mints.
+/// Mints and their oracles may be passed in either order.
+pub fn internal_swap_account_metas(
+    pool_registry: Pubkey,
+    mint_one: Pubkey,
+    mint_two: Pubkey,
+    mint_one_oracle: Pubkey,
+    mint_two_oracle: Pubkey,
+) -> Vec<AccountMeta> {
     // Normalize mints and oracles
     let (m1, m2) = Pair::normalize_mint_order(mint_one, mint_two);
     let (oracle1, oracle2) = if m1 != mint_one {
@@ -403,7 +459,7 @@ pub fn internal_swap(
     let ssl_b_main_token = get_associated_token_address(&ssl_pool_b_signer, &m2);
     let ssl_a_secondary_token = get_associated_token_address(&ssl_pool_a_signer, &m2);
     let ssl_b_secondary_token = get_associated_token_address(&ssl_pool_b_signer, &m1);
-    let accounts = gfx_ssl_v2_interface::accounts::InternalSwap {
+    gfx_ssl_v2_interface::accounts::InternalSwap {
         pair,
         pool_registry,
         ssl_a_main_token,
@@ -419,12 +475,7 @@ pub fn internal_swap(
         event_emitter: EventEmitter::address(),
         token_program: token::ID,
     }
-    .to_account_metas(None);
-    Instruction {
-        program_id: gfx_ssl_v2_interface::ID,
-        accounts,
-        data,
-    }
+    .to_account_metas(None)
 }
 
 pub fn claim_fees(pool_registry: Pubkey, owner: Pubkey, mint: Pubkey) -> Instruction {
@@ -583,3 +634,316 @@ pub fn swap(
         data,
     }
 }
+
+/// Like [swap], but with every account given explicitly instead of derived from the mints,
+/// e.g. to substitute vaults in tests.
+pub fn swap_with_accounts(
+    amount_in: u64,
+    min_out: u64,
+    accounts: gfx_ssl_v2_interface::accounts::Swap,
+) -> Instruction {
+    let data = gfx_ssl_v2_interface::instruction::Swap { amount_in, min_out }.data();
+
+    Instruction {
+        program_id: gfx_ssl_v2_interface::ID,
+        accounts: accounts.to_account_metas(None),
+        data,
+    }
+}
+
+/// Quote a swap without moving any tokens. Meant to be simulated,
+/// see [decode_quote_return_data] for reading the result.
+pub fn quote(
+    amount_in: u64,
+    pool_registry: Pubkey,
+    mint_in: Pubkey,
+    mint_out: Pubkey,
+    input_token_oracle: Pubkey,
+    output_token_oracle: Pubkey,
+) -> Instruction {
+    let data = gfx_ssl_v2_interface::instruction::Quote {
+        amount_in,
+        bband: None,
+    }
+    .data();
+
+    Instruction {
+        program_id: gfx_ssl_v2_interface::ID,
+        accounts: get_account_metas_for_quote(
+            pool_registry,
+            mint_in,
+            mint_out,
+            input_token_oracle,
+            output_token_oracle,
+        ),
+        data,
+    }
+}
+
+/// Decode the return data of a [quote] instruction into the output amount and the fee,
+/// both native amounts of the output mint.
+pub fn decode_quote_return_data(data: &[u8]) -> Option<(u64, u64)> {
+    if data.len() != 16 {
+        return None;
+    }
+    let amount_out = u64::from_le_bytes(data[..8].try_into().unwrap());
+    let fee = u64::from_le_bytes(data[8..].try_into().unwrap());
+    Some((amount_out, fee))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::AnchorDeserialize;
+
+    /// Returns two mints, already in normalized order.
+    fn normalized_mints() -> (Pubkey, Pubkey) {
+        Pair::normalize_mint_order(Pubkey::new_unique(), Pubkey::new_unique())
+    }
+
+    #[test]
+    fn swap_with_accounts_keeps_overrides() {
+        let pool_registry = Pubkey::new_unique();
+        let user_wallet = Pubkey::new_unique();
+        let (mint_in, mint_out) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (input_token_oracle, output_token_oracle) =
+            (Pubkey::new_unique(), Pubkey::new_unique());
+        let fee_destination = Pubkey::new_unique();
+        let derived = swap(
+            100,
+            90,
+            pool_registry,
+            user_wallet,
+            mint_in,
+            mint_out,
+            input_token_oracle,
+            output_token_oracle,
+            fee_destination,
+        );
+
+        let keys: Vec<Pubkey> = derived.accounts.iter().map(|meta| meta.pubkey).collect();
+        let fake_vault = Pubkey::new_unique();
+        let accounts = gfx_ssl_v2_interface::accounts::Swap {
+            pair: keys[0],
+            pool_registry: keys[1],
+            user_wallet: keys[2],
+            ssl_pool_in_signer: keys[3],
+            ssl_pool_out_signer: keys[4],
+            user_ata_in: keys[5],
+            user_ata_out: keys[6],
+            ssl_out_main_vault: fake_vault,
+            ssl_out_secondary_vault: keys[8],
+            ssl_in_main_vault: keys[9],
+            ssl_in_secondary_vault: keys[10],
+            ssl_out_fee_vault: keys[11],
+            fee_destination: keys[12],
+            output_token_price_history: keys[13],
+            output_token_oracle: keys[14],
+            input_token_price_history: keys[15],
+            input_token_oracle: keys[16],
+            event_emitter: keys[17],
+            token_program: keys[18],
+        };
+        let explicit = swap_with_accounts(100, 90, accounts);
+        assert_eq!(explicit.data, derived.data);
+        assert_eq!(explicit.accounts.len(), derived.accounts.len());
+        for (i, (explicit, derived)) in explicit.accounts.iter().zip(&derived.accounts).enumerate()
+        {
+            if i == 7 {
+                assert_eq!(explicit.pubkey, fake_vault);
+            } else {
+                assert_eq!(explicit, derived);
+            }
+        }
+    }
+
+    #[test]
+    fn create_pair_normalizes_mint_order() {
+        let (mint_a, mint_b) = normalized_mints();
+        let (dest_a, dest_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let admin = Pubkey::new_unique();
+        let pool_registry = Pubkey::new_unique();
+        let ordered = create_pair(10, 20, admin, pool_registry, mint_a, mint_b, dest_a, dest_b);
+        let reversed = create_pair(20, 10, admin, pool_registry, mint_b, mint_a, dest_b, dest_a);
+        assert_eq!(ordered, reversed);
+
+        let data =
+            gfx_ssl_v2_interface::instruction::CreatePair::try_from_slice(&ordered.data[8..])
+                .unwrap();
+        assert_eq!((data.mint_one_fee_rate, data.mint_two_fee_rate), (10, 20));
+        assert_eq!(ordered.accounts[1].pubkey, mint_a);
+        assert_eq!(ordered.accounts[2].pubkey, mint_b);
+        assert_eq!(ordered.accounts[3].pubkey, dest_a);
+        assert_eq!(ordered.accounts[4].pubkey, dest_b);
+        assert_eq!(
+            ordered.accounts[5].pubkey,
+            Pair::address(pool_registry, mint_a, mint_b)
+        );
+    }
+
+    #[test]
+    fn config_pair_normalizes_mint_order() {
+        let (mint_a, mint_b) = normalized_mints();
+        let (dest_a, dest_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let admin = Pubkey::new_unique();
+        let pool_registry = Pubkey::new_unique();
+        let ordered = ConfigPairParams::new(mint_a, mint_b)
+            .normal_fee(mint_a, 10)
+            .unwrap()
+            .fee_destination(mint_b, dest_b)
+            .unwrap();
+        let reversed = ConfigPairParams::new(mint_b, mint_a)
+            .fee_destination(mint_b, dest_b)
+            .unwrap()
+            .normal_fee(mint_a, 10)
+            .unwrap();
+        assert_eq!(ordered, reversed);
+        let ordered = config_pair(admin, pool_registry, ordered);
+        let reversed = config_pair(admin, pool_registry, reversed);
+        assert_eq!(ordered, reversed);
+
+        let data =
+            gfx_ssl_v2_interface::instruction::ConfigPair::try_from_slice(&ordered.data[8..])
+                .unwrap();
+        assert_eq!(
+            (data.mint_one_fee_rate, data.mint_two_fee_rate),
+            (Some(10), None)
+        );
+        assert_eq!(ordered.accounts[0].pubkey, Pubkey::default());
+        assert_eq!(ordered.accounts[1].pubkey, dest_b);
+        assert_eq!(
+            ordered.accounts[2].pubkey,
+            Pair::address(pool_registry, mint_a, mint_b)
+        );
+
+        let both = ConfigPairParams::new(mint_b, mint_a)
+            .fee_destination(mint_a, dest_a)
+            .unwrap();
+        let ix = config_pair(admin, pool_registry, both);
+        assert_eq!(ix.accounts[0].pubkey, dest_a);
+        assert!(ConfigPairParams::new(mint_a, mint_b)
+            .normal_fee(Pubkey::new_unique(), 10)
+            .is_err());
+    }
+
+    #[test]
+    fn config_pair_fees_apply_to_output_mint() {
+        let (mint_a, mint_b) = normalized_mints();
+        for (mint_in, mint_out) in [(mint_a, mint_b), (mint_b, mint_a)] {
+            let fee_destination = Pubkey::new_unique();
+            let params = ConfigPairParams::new(mint_in, mint_out)
+                .normal_fee(mint_out, 25)
+                .unwrap()
+                .fee_destination(mint_out, fee_destination)
+                .unwrap();
+            // As the program stores the configuration
+            let pair = Pair {
+                mints: params.mints(),
+                fee_rates: (
+                    params.fee_rates.0.unwrap_or_default(),
+                    params.fee_rates.1.unwrap_or_default(),
+                ),
+                fee_collector: (
+                    params.fee_destinations.0.unwrap_or_default(),
+                    params.fee_destinations.1.unwrap_or_default(),
+                ),
+                ..Default::default()
+            };
+            let (fee_rate, fee_collector, _) = pair.find_fee_attrs(mint_in, mint_out).unwrap();
+            assert_eq!(fee_rate, rust_decimal::Decimal::new(25, 4));
+            assert_eq!(fee_collector, fee_destination);
+            let (fee_rate, _, _) = pair.find_fee_attrs(mint_out, mint_in).unwrap();
+            assert_eq!(fee_rate, rust_decimal::Decimal::ZERO);
+        }
+    }
+
+    #[test]
+    fn internal_swap_normalizes_mint_order() {
+        let (mint_a, mint_b) = normalized_mints();
+        let (oracle_a, oracle_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let pool_registry = Pubkey::new_unique();
+        let ordered = internal_swap(pool_registry, mint_a, mint_b, oracle_a, oracle_b);
+        let reversed = internal_swap(pool_registry, mint_b, mint_a, oracle_b, oracle_a);
+        assert_eq!(ordered, reversed);
+
+        let ssl_a_signer = SSLPool::signer_address(pool_registry, mint_a);
+        let ssl_b_signer = SSLPool::signer_address(pool_registry, mint_b);
+        let accounts = &ordered.accounts;
+        assert_eq!(
+            accounts[1].pubkey,
+            Pair::address(pool_registry, mint_a, mint_b)
+        );
+        assert_eq!(
+            accounts[2].pubkey,
+            get_associated_token_address(&ssl_a_signer, &mint_a)
+        );
+        assert_eq!(
+            accounts[3].pubkey,
+            get_associated_token_address(&ssl_b_signer, &mint_b)
+        );
+        assert_eq!(
+            accounts[4].pubkey,
+            get_associated_token_address(&ssl_a_signer, &mint_b)
+        );
+        assert_eq!(
+            accounts[5].pubkey,
+            get_associated_token_address(&ssl_b_signer, &mint_a)
+        );
+        assert_eq!(
+            accounts[6].pubkey,
+            OraclePriceHistory::address(&pool_registry, &oracle_a)
+        );
+        assert_eq!(accounts[7].pubkey, oracle_a);
+        assert_eq!(
+            accounts[8].pubkey,
+            OraclePriceHistory::address(&pool_registry, &oracle_b)
+        );
+        assert_eq!(accounts[9].pubkey, oracle_b);
+    }
+
+    #[test]
+    fn internal_swap_vaults_and_histories_are_writable() {
+        let (mint_a, mint_b) = normalized_mints();
+        let pool_registry = Pubkey::new_unique();
+        // Pass the mints in reverse, the metas should still line up with `pair.mints`
+        let accounts = internal_swap_account_metas(
+            pool_registry,
+            mint_b,
+            mint_a,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let writable: Vec<_> = accounts
+            .iter()
+            .enumerate()
+            .filter(|(_, meta)| meta.is_writable)
+            .map(|(idx, _)| idx)
+            .collect();
+        // pair, both main vaults, both secondary vaults, both price histories, event emitter
+        assert_eq!(writable, vec![1, 2, 3, 4, 5, 6, 8, 12]);
+        assert!(accounts.iter().all(|meta| !meta.is_signer));
+
+        let ssl_a_signer = SSLPool::signer_address(pool_registry, mint_a);
+        let ssl_b_signer = SSLPool::signer_address(pool_registry, mint_b);
+        assert_eq!(accounts[10].pubkey, ssl_a_signer);
+        assert_eq!(accounts[11].pubkey, ssl_b_signer);
+        // Each pool's secondary vault holds the other pool's main mint
+        assert_eq!(
+            accounts[4].pubkey,
+            get_associated_token_address(&ssl_a_signer, &mint_b)
+        );
+        assert_eq!(
+            accounts[5].pubkey,
+            get_associated_token_address(&ssl_b_signer, &mint_a)
+        );
+    }
+
+    #[test]
+    fn decodes_quote_return_data() {
+        let mut data = 1_000u64.to_le_bytes().to_vec();
+        data.extend_from_slice(&3u64.to_le_bytes());
+        assert_eq!(decode_quote_return_data(&data), Some((1_000, 3)));
+        assert_eq!(decode_quote_return_data(&data[..8]), None);
+        assert_eq!(decode_quote_return_data(&[]), None);
+    }
+}
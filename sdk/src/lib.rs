@@ -1,7 +1,12 @@
+pub mod discriminator;
 pub mod error;
 pub mod instructions;
+pub mod lookup_table;
+pub mod oracle;
+pub mod rebalance;
 
 pub mod state;
 mod utils;
 
 pub use gfx_ssl_v2_interface::{self as anchor, id, ID};
+pub use instructions::ConfigPairParams;
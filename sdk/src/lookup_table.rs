@@ -0,0 +1,149 @@
+use anchor_lang::solana_program::{instruction::Instruction, pubkey::Pubkey};
+use anchor_spl::{associated_token::get_associated_token_address, token};
+use gfx_ssl_v2_interface::{EventEmitter, OraclePriceHistory, Pair, SSLPool};
+use solana_client::{nonblocking::rpc_client::RpcClient, rpc_client};
+use solana_sdk::{
+    address_lookup_table::{
+        instruction::{create_lookup_table, extend_lookup_table},
+        state::AddressLookupTable,
+    },
+    address_lookup_table_account::AddressLookupTableAccount,
+};
+
+use crate::error::{GfxSslSdkError, Result};
+
+/// Every account used by a swap on `pair`, in either direction,
+/// excluding the user's wallet and token accounts.
+/// The oracles correspond to `pair.mints.0` and `pair.mints.1` respectively.
+///
+/// Useful as the contents of an address lookup table, so that swaps
+/// fit in larger transactions, e.g. multi-hop routes.
+pub fn pair_lookup_table_addresses(
+    pool_registry: Pubkey,
+    pair: &Pair,
+    mint_one_oracle: Pubkey,
+    mint_two_oracle: Pubkey,
+) -> Vec<Pubkey> {
+    let (mint_one, mint_two) = pair.mints;
+    let ssl_pool_one_signer = SSLPool::signer_address(pool_registry, mint_one);
+    let ssl_pool_two_signer = SSLPool::signer_address(pool_registry, mint_two);
+    vec![
+        Pair::address(pool_registry, mint_one, mint_two),
+        pool_registry,
+        ssl_pool_one_signer,
+        ssl_pool_two_signer,
+        get_associated_token_address(&ssl_pool_one_signer, &mint_one),
+        get_associated_token_address(&ssl_pool_one_signer, &mint_two),
+        get_associated_token_address(&ssl_pool_two_signer, &mint_two),
+        get_associated_token_address(&ssl_pool_two_signer, &mint_one),
+        get_associated_token_address(&pool_registry, &mint_one),
+        get_associated_token_address(&pool_registry, &mint_two),
+        pair.fee_collector.0,
+        pair.fee_collector.1,
+        OraclePriceHistory::address(&pool_registry, &mint_one_oracle),
+        OraclePriceHistory::address(&pool_registry, &mint_two_oracle),
+        mint_one_oracle,
+        mint_two_oracle,
+        EventEmitter::address(),
+        token::ID,
+    ]
+}
+
+/// Instructions to create an address lookup table and populate it with `addresses`,
+/// along with the address of the new lookup table.
+///
+/// `recent_slot` must be a recent slot, and is part of the lookup table's address derivation.
+/// A lookup table can only be used in transactions once the slot in which
+/// it was extended has passed.
+pub fn create_lookup_table_with_addresses(
+    authority: Pubkey,
+    payer: Pubkey,
+    recent_slot: u64,
+    addresses: Vec<Pubkey>,
+) -> (Vec<Instruction>, Pubkey) {
+    let (create_ix, lookup_table) = create_lookup_table(authority, payer, recent_slot);
+    let extend_ix = extend_lookup_table(lookup_table, authority, Some(payer), addresses);
+    (vec![create_ix, extend_ix], lookup_table)
+}
+
+pub async fn get_address_lookup_table(
+    address: &Pubkey,
+    client: &RpcClient,
+) -> Result<AddressLookupTableAccount> {
+    let data = client
+        .get_account_data(address)
+        .await
+        .map_err(|_| GfxSslSdkError::AccountNotFound(*address))?;
+    deserialize_lookup_table(address, &data)
+}
+
+pub fn get_address_lookup_table_blocking(
+    address: &Pubkey,
+    client: &rpc_client::RpcClient,
+) -> Result<AddressLookupTableAccount> {
+    let data = client
+        .get_account_data(address)
+        .map_err(|_| GfxSslSdkError::AccountNotFound(*address))?;
+    deserialize_lookup_table(address, &data)
+}
+
+fn deserialize_lookup_table(address: &Pubkey, data: &[u8]) -> Result<AddressLookupTableAccount> {
+    let lookup_table = AddressLookupTable::deserialize(data).map_err(|_| {
+        GfxSslSdkError::DeserializeFailure(*address, "AddressLookupTable".to_string())
+    })?;
+    Ok(AddressLookupTableAccount {
+        key: *address,
+        addresses: lookup_table.addresses.to_vec(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instructions::swap;
+
+    #[test]
+    fn lookup_table_covers_swaps_in_both_directions() {
+        let pool_registry = Pubkey::new_unique();
+        let mints = Pair::normalize_mint_order(Pubkey::new_unique(), Pubkey::new_unique());
+        let pair = Pair {
+            pool_registry,
+            mints,
+            fee_collector: (Pubkey::new_unique(), Pubkey::new_unique()),
+            ..Default::default()
+        };
+        let oracles = (Pubkey::new_unique(), Pubkey::new_unique());
+        let addresses = pair_lookup_table_addresses(pool_registry, &pair, oracles.0, oracles.1);
+
+        let user_wallet = Pubkey::new_unique();
+        for (mint_in, mint_out, oracle_in, oracle_out) in [
+            (mints.0, mints.1, oracles.0, oracles.1),
+            (mints.1, mints.0, oracles.1, oracles.0),
+        ] {
+            let (_, fee_destination, _) = pair.find_fee_attrs(mint_in, mint_out).unwrap();
+            let ix = swap(
+                1,
+                0,
+                pool_registry,
+                user_wallet,
+                mint_in,
+                mint_out,
+                oracle_in,
+                oracle_out,
+                fee_destination,
+            );
+            let user_accounts = [
+                user_wallet,
+                get_associated_token_address(&user_wallet, &mint_in),
+                get_associated_token_address(&user_wallet, &mint_out),
+            ];
+            for meta in &ix.accounts {
+                assert!(
+                    user_accounts.contains(&meta.pubkey) || addresses.contains(&meta.pubkey),
+                    "{} is missing from the lookup table",
+                    meta.pubkey
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,212 @@
+use anchor_lang::solana_program::{pubkey, pubkey::Pubkey};
+use gfx_ssl_v2_interface::OracleType;
+use rust_decimal::Decimal;
+use switchboard_v2::AggregatorAccountData;
+
+use crate::error::{GfxSslSdkError, Result};
+
+/// Owner of Pyth price accounts on mainnet-beta.
+pub const PYTH_MAINNET_PROGRAM_ID: Pubkey = pubkey!("FsJ3A3u2vn5cTVofAjvy6y5kwABJAqYWpe4975bi2epH");
+/// Owner of Pyth price accounts on devnet.
+pub const PYTH_DEVNET_PROGRAM_ID: Pubkey = pubkey!("gSbePebfvPy7tRqimPoVecS2UsBvYv46ynrzWocc92s");
+/// Owner of Switchboard v2 aggregator accounts on mainnet-beta.
+pub const SWITCHBOARD_V2_MAINNET_PROGRAM_ID: Pubkey =
+    pubkey!("SW1TCH7qEPTdLsDHRgPuMQjbQxKdH2aBStViMFnt64f");
+/// Owner of Switchboard v2 aggregator accounts on devnet.
+pub const SWITCHBOARD_V2_DEVNET_PROGRAM_ID: Pubkey =
+    pubkey!("2TfB33aLaneQb5TNVwyDz3jSZXS6jdW2ARw1Dgf84XCG");
+
+/// Every Pyth account starts with this magic number, little-endian.
+pub const PYTH_MAGIC: u32 = 0xa1b2c3d4;
+
+/// Programs that may own an oracle account of the given type.
+pub fn oracle_program_ids(oracle_type: OracleType) -> &'static [Pubkey] {
+    match oracle_type {
+        OracleType::Pyth => &[PYTH_MAINNET_PROGRAM_ID, PYTH_DEVNET_PROGRAM_ID],
+        OracleType::Switchboardv2 => &[
+            SWITCHBOARD_V2_MAINNET_PROGRAM_ID,
+            SWITCHBOARD_V2_DEVNET_PROGRAM_ID,
+        ],
+        OracleType::Uninitialized | OracleType::Invalid => &[],
+    }
+}
+
+/// Check that the account at `oracle`, with the given `owner` and `data`,
+/// looks like an oracle of type `oracle_type`.
+pub fn verify_oracle_account(
+    oracle: &Pubkey,
+    oracle_type: OracleType,
+    owner: &Pubkey,
+    data: &[u8],
+) -> Result<()> {
+    if !oracle_program_ids(oracle_type).contains(owner) {
+        return Err(GfxSslSdkError::InvalidOracle(
+            *oracle,
+            format!("{} is not an owner of {} oracles", owner, oracle_type),
+        ));
+    }
+    if let OracleType::Pyth = oracle_type {
+        let magic = data
+            .get(..4)
+            .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()));
+        if magic != Some(PYTH_MAGIC) {
+            return Err(GfxSslSdkError::InvalidOracle(
+                *oracle,
+                "missing the Pyth magic number".to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Read the current price from raw oracle account data, without checking staleness.
+pub fn oracle_price(oracle: &Pubkey, oracle_type: OracleType, data: &[u8]) -> Result<Decimal> {
+    let invalid = |reason: &str| GfxSslSdkError::InvalidOracle(*oracle, reason.to_string());
+    match oracle_type {
+        OracleType::Pyth => {
+            let price_account = pyth_sdk_solana::state::load_price_account(data)
+                .map_err(|_| invalid("not a Pyth price account"))?;
+            let price = price_account.to_price_feed(oracle).get_price_unchecked();
+            if price.expo > 0 {
+                return Err(invalid("unsupported positive price exponent"));
+            }
+            Ok(Decimal::new(price.price, price.expo.unsigned_abs()))
+        }
+        OracleType::Switchboardv2 => {
+            let aggregator = AggregatorAccountData::new_from_bytes(data)
+                .map_err(|_| invalid("not a Switchboard v2 aggregator"))?;
+            let result = aggregator
+                .get_result()
+                .map_err(|_| invalid("aggregator has no result"))?;
+            Decimal::try_from_i128_with_scale(result.mantissa, result.scale)
+                .map_err(|_| invalid("aggregator result out of range"))
+        }
+        OracleType::Uninitialized | OracleType::Invalid => Err(invalid("unknown oracle type")),
+    }
+}
+
+/// Check that `price` lies within the optional, inclusive bounds.
+/// Without a lower bound, the price must still be positive.
+pub fn check_price_bounds(
+    price: Decimal,
+    min_price: Option<Decimal>,
+    max_price: Option<Decimal>,
+) -> std::result::Result<(), String> {
+    if price <= Decimal::ZERO {
+        return Err(format!("price {} is not positive", price));
+    }
+    if let Some(min_price) = min_price {
+        if price < min_price {
+            return Err(format!("price {} is below {}", price, min_price));
+        }
+    }
+    if let Some(max_price) = max_price {
+        if price > max_price {
+            return Err(format!("price {} is above {}", price, max_price));
+        }
+    }
+    Ok(())
+}
+
+/// Price impact of a swap in basis points, i.e. how far the value of `amount_out` falls short of
+/// the value of `amount_in`, both valued at oracle prices. Amounts are native and converted with
+/// the mints' decimals. Negative if the swap beats the oracle prices.
+pub fn price_impact_bps(
+    amount_in: u64,
+    decimals_in: u8,
+    price_in: Decimal,
+    amount_out: u64,
+    decimals_out: u8,
+    price_out: Decimal,
+) -> Option<Decimal> {
+    let value_in = Decimal::try_new(amount_in.try_into().ok()?, decimals_in.into())
+        .ok()?
+        .checked_mul(price_in)?;
+    let value_out = Decimal::try_new(amount_out.try_into().ok()?, decimals_out.into())
+        .ok()?
+        .checked_mul(price_out)?;
+    if value_in <= Decimal::ZERO {
+        return None;
+    }
+    (value_in - value_out)
+        .checked_div(value_in)?
+        .checked_mul(Decimal::from(10_000))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_oracle_of_another_type() {
+        let oracle = Pubkey::new_unique();
+        let data = PYTH_MAGIC.to_le_bytes();
+        assert!(
+            verify_oracle_account(&oracle, OracleType::Pyth, &PYTH_MAINNET_PROGRAM_ID, &data)
+                .is_ok()
+        );
+        assert!(verify_oracle_account(
+            &oracle,
+            OracleType::Switchboardv2,
+            &PYTH_MAINNET_PROGRAM_ID,
+            &data
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn price_bounds() {
+        let price = Decimal::new(2_050, 2);
+        assert!(check_price_bounds(price, None, None).is_ok());
+        assert!(
+            check_price_bounds(price, Some(Decimal::from(20)), Some(Decimal::from(21))).is_ok()
+        );
+        assert!(check_price_bounds(price, Some(price), Some(price)).is_ok());
+        assert!(check_price_bounds(price, Some(Decimal::from(21)), None).is_err());
+        assert!(check_price_bounds(price, None, Some(Decimal::from(20))).is_err());
+        assert!(check_price_bounds(Decimal::ZERO, None, None).is_err());
+        assert!(check_price_bounds(Decimal::new(-1, 0), None, None).is_err());
+    }
+
+    #[test]
+    fn rejects_pyth_account_without_magic() {
+        let oracle = Pubkey::new_unique();
+        assert!(
+            verify_oracle_account(&oracle, OracleType::Pyth, &PYTH_DEVNET_PROGRAM_ID, &[0; 4])
+                .is_err()
+        );
+        assert!(
+            verify_oracle_account(&oracle, OracleType::Pyth, &PYTH_DEVNET_PROGRAM_ID, &[]).is_err()
+        );
+    }
+
+    #[test]
+    fn price_impact() {
+        // 1 SOL at $20 for 19.8 USDC at $1
+        let impact = price_impact_bps(
+            1_000_000_000,
+            9,
+            Decimal::from(20),
+            19_800_000,
+            6,
+            Decimal::ONE,
+        );
+        assert_eq!(impact, Some(Decimal::from(100)));
+        let impact = price_impact_bps(
+            19_800_000,
+            6,
+            Decimal::ONE,
+            1_000_000_000,
+            9,
+            Decimal::from(20),
+        );
+        assert_eq!(
+            impact.map(|bps| bps.round_dp(4)),
+            Some(Decimal::new(-1_010_101, 4))
+        );
+        assert_eq!(
+            price_impact_bps(0, 9, Decimal::ONE, 1, 6, Decimal::ONE),
+            None
+        );
+    }
+}
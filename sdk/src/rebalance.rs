@@ -0,0 +1,228 @@
+use anchor_lang::solana_program::pubkey::Pubkey;
+use gfx_ssl_v2_interface::{utils::token_amount, Pair, PoolRegistry, SSLPool};
+use rust_decimal::Decimal;
+
+use crate::error::{GfxSslSdkError, Result};
+
+/// Native token balances of the four vaults touched by an internal swap.
+///
+/// "A" and "B" refer to the mints at `pair.mints.0` and `pair.mints.1` respectively.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct InternalSwapVaultBalances {
+    /// Token A held by SSL pool A
+    pub ssl_a_main: u64,
+    /// Token B held by SSL pool B
+    pub ssl_b_main: u64,
+    /// Token B held by SSL pool A
+    pub ssl_a_secondary: u64,
+    /// Token A held by SSL pool B
+    pub ssl_b_secondary: u64,
+}
+
+/// A heuristic upper bound on the native amounts moved by an internal swap.
+///
+/// The program's internal swap logic isn't part of this crate. These amounts assume a swap at
+/// fair value of the smaller secondary vault, which the program may not move in full, e.g.
+/// because of fees or its own limits. Don't rely on them as the amounts actually swapped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InternalSwapPlan {
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    /// At most this much token B moved from SSL pool A's secondary vault
+    /// to SSL pool B's main vault.
+    pub max_amount_b_to_ssl_b: u64,
+    /// At most this much token A moved from SSL pool B's secondary vault
+    /// to SSL pool A's main vault.
+    pub max_amount_a_to_ssl_a: u64,
+}
+
+/// Determine whether an `internal_swap` on `pair` is warranted.
+///
+/// A pool is imbalanced when the USD value of its secondary vault, relative to
+/// its main vault, exceeds the pool registry's max pool token ratio for swaps
+/// from the secondary token's asset type into the main token's asset type.
+/// Returns `Some` only if at least one of the two pools is imbalanced and both
+/// pools hold enough secondary tokens to move a non-zero amount at the given
+/// USD prices. See [InternalSwapPlan] for how the amounts are bounded.
+pub fn internal_swap_opportunity(
+    pool_registry: &PoolRegistry,
+    pair: &Pair,
+    balances: InternalSwapVaultBalances,
+    price_a: Decimal,
+    price_b: Decimal,
+) -> Result<Option<InternalSwapPlan>> {
+    let (mint_a, mint_b) = pair.mints;
+    let ssl_a = find_pool(pool_registry, mint_a)?;
+    let ssl_b = find_pool(pool_registry, mint_b)?;
+    if price_a <= Decimal::ZERO || price_b <= Decimal::ZERO {
+        return Ok(None);
+    }
+    let decimals_a = ssl_a.mint_decimals as u32;
+    let decimals_b = ssl_b.mint_decimals as u32;
+
+    let ssl_a_main_value = token_amount::to_ui(balances.ssl_a_main, decimals_a) * price_a;
+    let ssl_a_secondary_value = token_amount::to_ui(balances.ssl_a_secondary, decimals_b) * price_b;
+    let ssl_b_main_value = token_amount::to_ui(balances.ssl_b_main, decimals_b) * price_b;
+    let ssl_b_secondary_value = token_amount::to_ui(balances.ssl_b_secondary, decimals_a) * price_a;
+
+    let ssl_a_max_ratio = pool_registry
+        .max_pool_token_ratio(&ssl_b.asset_type(), &ssl_a.asset_type())
+        .map_err(|_| GfxSslSdkError::InvalidAssetType(mint_a))?;
+    let ssl_b_max_ratio = pool_registry
+        .max_pool_token_ratio(&ssl_a.asset_type(), &ssl_b.asset_type())
+        .map_err(|_| GfxSslSdkError::InvalidAssetType(mint_b))?;
+    if !exceeds_ratio(ssl_a_secondary_value, ssl_a_main_value, ssl_a_max_ratio)
+        && !exceeds_ratio(ssl_b_secondary_value, ssl_b_main_value, ssl_b_max_ratio)
+    {
+        return Ok(None);
+    }
+
+    // Heuristic: swap at fair value, limited by whichever secondary vault is worth less.
+    let swap_value = ssl_a_secondary_value.min(ssl_b_secondary_value);
+    let max_amount_b_to_ssl_b = token_amount::to_native(swap_value / price_b, decimals_b);
+    let max_amount_a_to_ssl_a = token_amount::to_native(swap_value / price_a, decimals_a);
+    if max_amount_b_to_ssl_b == 0 || max_amount_a_to_ssl_a == 0 {
+        return Ok(None);
+    }
+    Ok(Some(InternalSwapPlan {
+        mint_a,
+        mint_b,
+        max_amount_b_to_ssl_b,
+        max_amount_a_to_ssl_a,
+    }))
+}
+
+fn find_pool(pool_registry: &PoolRegistry, mint: Pubkey) -> Result<&SSLPool> {
+    pool_registry
+        .find_pool(mint)
+        .map_err(|_| GfxSslSdkError::PoolNotFound(mint))
+}
+
+fn exceeds_ratio(secondary_value: Decimal, main_value: Decimal, max_ratio: Decimal) -> bool {
+    match secondary_value.checked_div(main_value) {
+        Some(ratio) => ratio > max_ratio,
+        None => !secondary_value.is_zero(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gfx_ssl_v2_interface::{AssetType, SSLMathParams, SSLPoolStatus};
+
+    const DECIMALS_A: u8 = 9;
+    const DECIMALS_B: u8 = 6;
+
+    fn setup() -> (PoolRegistry, Pair) {
+        let (mint_a, mint_b) =
+            Pair::normalize_mint_order(Pubkey::new_unique(), Pubkey::new_unique());
+        let mut pool_registry = PoolRegistry::default();
+        pool_registry.entries[0] = SSLPool::new(
+            SSLPoolStatus::Active,
+            AssetType::BlueChip,
+            mint_a,
+            DECIMALS_A,
+            0,
+            Pubkey::new_unique(),
+            0,
+            SSLMathParams::default(),
+        );
+        pool_registry.entries[1] = SSLPool::new(
+            SSLPoolStatus::Active,
+            AssetType::Stable,
+            mint_b,
+            DECIMALS_B,
+            0,
+            Pubkey::new_unique(),
+            0,
+            SSLMathParams::default(),
+        );
+        pool_registry.num_entries = 2;
+        // 10% in both directions
+        pool_registry
+            .set_max_pool_token_ratio(&AssetType::BlueChip, &AssetType::Stable, 1_000)
+            .unwrap();
+        pool_registry
+            .set_max_pool_token_ratio(&AssetType::Stable, &AssetType::BlueChip, 1_000)
+            .unwrap();
+        let pair = Pair {
+            pool_registry: Pubkey::new_unique(),
+            mints: (mint_a, mint_b),
+            ..Default::default()
+        };
+        (pool_registry, pair)
+    }
+
+    #[test]
+    fn balanced_pools_have_no_opportunity() {
+        let (pool_registry, pair) = setup();
+        // Pool A: 100 A ($2000) main, 50 B ($50) secondary -> 2.5%
+        // Pool B: 10_000 B ($10_000) main, 1 A ($20) secondary -> 0.2%
+        let balances = InternalSwapVaultBalances {
+            ssl_a_main: 100_000_000_000,
+            ssl_b_main: 10_000_000_000,
+            ssl_a_secondary: 50_000_000,
+            ssl_b_secondary: 1_000_000_000,
+        };
+        let plan = internal_swap_opportunity(
+            &pool_registry,
+            &pair,
+            balances,
+            Decimal::from(20),
+            Decimal::ONE,
+        )
+        .unwrap();
+        assert_eq!(plan, None);
+    }
+
+    #[test]
+    fn imbalanced_pool_has_opportunity() {
+        let (pool_registry, pair) = setup();
+        // Pool A: 100 A ($2000) main, 500 B ($500) secondary -> 25%
+        // Pool B: 10_000 B ($10_000) main, 10 A ($200) secondary -> 2%
+        let balances = InternalSwapVaultBalances {
+            ssl_a_main: 100_000_000_000,
+            ssl_b_main: 10_000_000_000,
+            ssl_a_secondary: 500_000_000,
+            ssl_b_secondary: 10_000_000_000,
+        };
+        let plan = internal_swap_opportunity(
+            &pool_registry,
+            &pair,
+            balances,
+            Decimal::from(20),
+            Decimal::ONE,
+        )
+        .unwrap();
+        // Limited by pool B's $200 of token A
+        assert_eq!(
+            plan,
+            Some(InternalSwapPlan {
+                mint_a: pair.mints.0,
+                mint_b: pair.mints.1,
+                max_amount_b_to_ssl_b: 200_000_000,
+                max_amount_a_to_ssl_a: 10_000_000_000,
+            })
+        );
+    }
+
+    #[test]
+    fn imbalanced_pool_without_counterparty_has_no_opportunity() {
+        let (pool_registry, pair) = setup();
+        let balances = InternalSwapVaultBalances {
+            ssl_a_main: 100_000_000_000,
+            ssl_b_main: 10_000_000_000,
+            ssl_a_secondary: 500_000_000,
+            ssl_b_secondary: 0,
+        };
+        let plan = internal_swap_opportunity(
+            &pool_registry,
+            &pair,
+            balances,
+            Decimal::from(20),
+            Decimal::ONE,
+        )
+        .unwrap();
+        assert_eq!(plan, None);
+    }
+}
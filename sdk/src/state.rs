@@ -1,9 +1,19 @@
-use anchor_lang::{prelude::AccountMeta, solana_program::pubkey::Pubkey, ToAccountMetas};
+use anchor_lang::{
+    prelude::AccountMeta, solana_program::pubkey::Pubkey, AccountDeserialize, ToAccountMetas,
+};
 use anchor_spl::{associated_token::get_associated_token_address, token};
 pub use gfx_ssl_v2_interface::state::*;
-use solana_client::{nonblocking::rpc_client::RpcClient, rpc_client};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    nonblocking::rpc_client::RpcClient,
+    rpc_client,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+};
+use solana_sdk::account::Account;
 
 use crate::{
+    discriminator::POOL_REGISTRY_DISCRIMINATOR,
     error::{GfxSslSdkError, Result},
     utils::{get_state, get_state_blocking},
 };
@@ -103,6 +113,54 @@ pub fn get_liquidity_account_blocking(
     get_state_blocking(address, client, "LiquidityAccount")
 }
 
+/// Selects every [PoolRegistry] owned by the SSLv2 program.
+fn pool_registry_accounts_config() -> RpcProgramAccountsConfig {
+    RpcProgramAccountsConfig {
+        filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+            0,
+            &POOL_REGISTRY_DISCRIMINATOR,
+        ))]),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+fn deserialize_pool_registries(
+    accounts: Vec<(Pubkey, Account)>,
+) -> Result<Vec<(Pubkey, PoolRegistry)>> {
+    accounts
+        .into_iter()
+        .map(|(address, account)| {
+            PoolRegistry::try_deserialize(&mut account.data.as_slice())
+                .map(|pool_registry| (address, pool_registry))
+                .map_err(|_| {
+                    GfxSslSdkError::DeserializeFailure(address, "PoolRegistry".to_string())
+                })
+        })
+        .collect()
+}
+
+/// Find every pool registry on the cluster, e.g. on devnet where there may be several.
+pub async fn find_all_pool_registries(client: &RpcClient) -> Result<Vec<(Pubkey, PoolRegistry)>> {
+    let accounts = client
+        .get_program_accounts_with_config(&crate::ID, pool_registry_accounts_config())
+        .await
+        .map_err(|e| GfxSslSdkError::RpcError(e.to_string()))?;
+    deserialize_pool_registries(accounts)
+}
+
+pub fn find_all_pool_registries_blocking(
+    client: &rpc_client::RpcClient,
+) -> Result<Vec<(Pubkey, PoolRegistry)>> {
+    let accounts = client
+        .get_program_accounts_with_config(&crate::ID, pool_registry_accounts_config())
+        .map_err(|e| GfxSslSdkError::RpcError(e.to_string()))?;
+    deserialize_pool_registries(accounts)
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn get_account_metas_for_swap(
     pool_registry: Pubkey,
@@ -151,3 +209,37 @@ pub fn get_account_metas_for_swap(
     }
     .to_account_metas(None)
 }
+
+pub fn get_account_metas_for_quote(
+    pool_registry: Pubkey,
+    mint_in: Pubkey,
+    mint_out: Pubkey,
+    input_token_oracle: Pubkey,
+    output_token_oracle: Pubkey,
+) -> Vec<AccountMeta> {
+    let pair = Pair::address(pool_registry, mint_in, mint_out);
+    let input_token_price_history =
+        OraclePriceHistory::address(&pool_registry, &input_token_oracle);
+    let output_token_price_history =
+        OraclePriceHistory::address(&pool_registry, &output_token_oracle);
+    let ssl_pool_in_signer = SSLPool::signer_address(pool_registry, mint_in);
+    let ssl_pool_out_signer = SSLPool::signer_address(pool_registry, mint_out);
+    let ssl_in_main_vault = get_associated_token_address(&ssl_pool_in_signer, &mint_in);
+    let ssl_in_secondary_vault = get_associated_token_address(&ssl_pool_in_signer, &mint_out);
+    let ssl_out_main_vault = get_associated_token_address(&ssl_pool_out_signer, &mint_out);
+    let ssl_out_secondary_vault = get_associated_token_address(&ssl_pool_out_signer, &mint_in);
+
+    gfx_ssl_v2_interface::accounts::Quote {
+        pair,
+        pool_registry,
+        ssl_out_main_vault,
+        ssl_out_secondary_vault,
+        ssl_in_main_vault,
+        ssl_in_secondary_vault,
+        output_token_price_history,
+        output_token_oracle,
+        input_token_price_history,
+        input_token_oracle,
+    }
+    .to_account_metas(None)
+}
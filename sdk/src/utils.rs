@@ -2,13 +2,13 @@ use anchor_lang::AccountDeserialize;
 use solana_client::{nonblocking::rpc_client::RpcClient, rpc_client};
 use solana_sdk::pubkey::Pubkey;
 
-use crate::error::{self, GfxSslSdkError};
+use crate::error::{GfxSslSdkError, Result};
 
 pub async fn get_state<T: AccountDeserialize>(
     address: &Pubkey,
     client: &RpcClient,
     type_name: &str,
-) -> error::Result<T> {
+) -> Result<T> {
     let data = client
         .get_account_data(address)
         .await
@@ -22,7 +22,7 @@ pub fn get_state_blocking<T: AccountDeserialize>(
     address: &Pubkey,
     client: &rpc_client::RpcClient,
     type_name: &str,
-) -> error::Result<T> {
+) -> Result<T> {
     let data = client
         .get_account_data(address)
         .map_err(|_| GfxSslSdkError::AccountNotFound(address.clone()))?;